@@ -34,6 +34,15 @@ gamma_table! {
     max_value: 128  // Limit to 50% brightness
 }
 
+// Generate a perceptually uniform LED dimming table using CIE L*
+gamma_table! {
+    name: LED_LIGHTNESS_TABLE,
+    entry_type: u8,
+    curve: cie_lightness,
+    size: 256,
+    max_value: 128  // Limit to 50% brightness
+}
+
 fn main() {
     println!("=== Gamma Encoding vs Decoding Comparison ===");
     println!("Gamma Encoding Table (input^gamma):");
@@ -54,6 +63,14 @@ fn main() {
         );
     }
 
+    println!("\n=== LED Lightness Table (CIE L*, brightness limited) ===");
+    for (i, &value) in LED_LIGHTNESS_TABLE.iter().take(16).enumerate() {
+        println!(
+            "  Input: {:3} -> Output: {:3} (max possible: 128)",
+            i, value
+        );
+    }
+
     // Demonstrate the difference between encoding and decoding
     let test_input = 128u8; // 50% input
     let encoded_value = GAMMA_ENCODING_TABLE[test_input as usize];
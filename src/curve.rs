@@ -0,0 +1,2040 @@
+//! Transfer curves that can be evaluated by [`gamma_table!`](crate::gamma_table).
+//!
+//! Every curve maps a normalized input in `0.0..=1.0` to a normalized output, which
+//! [`generate_table_values`](crate::generate_table_values) then scales by `max_value`.
+//! The default direction of each curve matches the power-law `input^gamma` table
+//! (encoded signal in, linear light out); `decoding: true` selects the inverse.
+
+mod companding;
+mod cube;
+mod dimmer;
+mod easing;
+mod expression;
+mod fit;
+mod gsdf;
+mod icc;
+mod log;
+mod measured;
+mod pipeline;
+mod tonemap;
+
+use companding::Companding;
+use cube::CubeCurve;
+pub(crate) use cube::{format_cube, CubeLut};
+use dimmer::Dimmer;
+use easing::Easing;
+pub(crate) use expression::Expression;
+pub(crate) use fit::Fit;
+use fit::FitModel;
+pub(crate) use icc::IccProfile;
+use icc::{Channel, IccCurve};
+use log::{AcesLog, CameraLog};
+pub(crate) use measured::Points;
+use measured::{Interpolation, Measured};
+pub(crate) use pipeline::Stage;
+use proc_macro2::Span;
+use std::path::{Path, PathBuf};
+use syn::{Error, LitStr};
+use tonemap::ToneMap;
+
+/// Numeric parameters consumed by the curves.
+const FLOAT_PARAMETERS: [&str; 21] = [
+    "gamma",
+    "knee",
+    "slope",
+    "offset",
+    "power",
+    "black_level",
+    "white_level",
+    "peak_nits",
+    "system_gamma",
+    "min_luminance",
+    "max_luminance",
+    "white",
+    "min_db",
+    "max_db",
+    "midpoint",
+    "steepness",
+    "expo",
+    "dead_zone",
+    "dot_gain",
+    "from_gamma",
+    "to_gamma",
+];
+
+/// Parameters that select the [`Piecewise`] curve when given without `curve`.
+const PIECEWISE_PARAMETERS: [&str; 4] = ["knee", "slope", "offset", "power"];
+
+/// Names accepted by the `curve` parameter, besides [`Dimmer::NAMES`], [`CameraLog::NAMES`],
+/// [`AcesLog::NAMES`], [`ToneMap::NAMES`], [`Easing::NAMES`] and [`Companding::NAMES`].
+const CURVE_NAMES: [&str; 21] = [
+    "piecewise",
+    "srgb",
+    "rec709",
+    "rec2020_10bit",
+    "rec2020_12bit",
+    "bt1886",
+    "pq",
+    "hlg",
+    "cie_lightness",
+    "gsdf",
+    "db_taper",
+    "dali",
+    "sigmoid",
+    "bipolar",
+    "dot_gain",
+    "expr",
+    "pipeline",
+    "measured",
+    "fit",
+    "icc",
+    "cube",
+];
+
+/// Exponent of the BT.1886 EOTF.
+const BT1886_GAMMA: f64 = 2.4;
+
+/// Luminance in cd/m² of a full-scale ST 2084 signal.
+const PQ_MAX_NITS: f64 = 10_000.0;
+/// ST 2084 constants, as exact rationals from the standard.
+const PQ_M1: f64 = 2610.0 / 16384.0;
+const PQ_M2: f64 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f64 = 3424.0 / 4096.0;
+const PQ_C2: f64 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f64 = 2392.0 / 4096.0 * 32.0;
+
+/// ARIB STD-B67 / BT.2100 HLG OETF constants.
+const HLG_A: f64 = 0.178_832_77;
+const HLG_B: f64 = 1.0 - 4.0 * HLG_A;
+const HLG_C: f64 = 0.559_910_73;
+
+/// CIE 1976 constants `ε = 216/24389` and `κ = 24389/27`.
+const CIE_EPSILON: f64 = 216.0 / 24389.0;
+const CIE_KAPPA: f64 = 24389.0 / 27.0;
+
+/// Highest IEC 62386 arc power level; level `1` is 0.1% and this level is 100%.
+const DALI_MAX_LEVEL: f64 = 254.0;
+
+/// Number of points at which a curve is sampled to check that it can be inverted.
+const INVERSE_SAMPLES: u32 = 4096;
+/// Bisection steps for an inverted curve, enough to halve the unit interval down to f64 precision.
+const INVERSE_ITERATIONS: u32 = 64;
+
+/// Largest mismatch between the two segments of a piecewise curve at its knee, relative to the
+/// linear segment's value there.
+const CONTINUITY_TOLERANCE: f64 = 1e-6;
+
+/// A transfer curve selected by the `gamma` and `curve` parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Curve {
+    /// Pure power law, `input^gamma`.
+    Power { gamma: f64 },
+    /// Linear segment below a knee, offset power law above it.
+    Piecewise(Piecewise),
+    /// ITU-R BT.1886 reference display EOTF with black and white luminance in cd/m².
+    Bt1886 { black_level: f64, white_level: f64 },
+    /// SMPTE ST 2084 perceptual quantizer, with luminance relative to `peak_nits`.
+    Pq { peak_nits: f64 },
+    /// ARIB STD-B67 Hybrid Log-Gamma, with the OOTF applied using `system_gamma`.
+    Hlg { system_gamma: f64 },
+    /// CIE 1976 lightness `L*`, including its linear segment near black.
+    CieLightness,
+    /// A vendor camera log encoding, with linear light normalized to the value at code `1.0`.
+    CameraLog(CameraLog),
+    /// An ACES log encoding, in ACES units where `1.0` maps to `max_value`.
+    AcesLog(AcesLog),
+    /// DICOM PS3.14 Grayscale Standard Display Function between two luminances in cd/m².
+    Gsdf {
+        min_luminance: f64,
+        max_luminance: f64,
+        /// JND indices of `min_luminance` and `max_luminance`, solved once for the whole table.
+        low_index: f64,
+        high_index: f64,
+    },
+    /// A tone-mapping operator over linear input `0.0..=white`, optionally followed by a power law.
+    ToneMap {
+        operator: ToneMap,
+        white: f64,
+        gamma: Option<f64>,
+    },
+    /// IEC 62386 (DALI) logarithmic dimming curve, with arc power level `0` switching off.
+    Dali,
+    /// A stage lighting dimmer curve from fader level to output level.
+    Dimmer(Dimmer),
+    /// Logistic contrast curve rescaled to pass through `0.0` and `1.0`, optionally followed by a
+    /// power law.
+    Sigmoid {
+        midpoint: f64,
+        steepness: f64,
+        gamma: Option<f64>,
+    },
+    /// An animation easing function from progress to eased value, which may overshoot `0.0..=1.0`.
+    Easing(Easing),
+    /// Power law applied to each half of a table centered on its middle index, with a dead zone
+    /// around the center, for signed output in `-1.0..=1.0`.
+    Bipolar { expo: f64, dead_zone: f64 },
+    /// Compensation for printing dot gain, given the gain of a 50% dot.
+    DotGain { dot_gain: f64 },
+    /// A user-supplied expression in the normalized input `x`.
+    Expression(Expression),
+    /// Volume taper in equal decibel steps from a mute floor at `min_db` up to `max_db`.
+    DbTaper { min_db: f64, max_db: f64 },
+    /// Interpolated through measured calibration points.
+    Measured(Measured),
+    /// A power law fitted to measured samples.
+    Fitted(Fit),
+    /// A tone response curve from an ICC profile.
+    Icc(IccCurve),
+    /// The red, green and blue curves of an RGB profile read without a `channel`, which
+    /// [`generate_gamma_table`](crate::generate_gamma_table) splits into a table each.
+    IccChannels(Vec<(Channel, IccCurve)>),
+    /// One column of a 1D `.cube` LUT.
+    Cube(CubeCurve),
+    /// G.711 codes and linear samples, with the bits of codes inverted on the wire when
+    /// `wire_format` is set.
+    Companding { law: Companding, wire_format: bool },
+    /// The numerical inverse of a monotonic curve, over the range of its output.
+    Inverse(Box<Curve>),
+    /// Stages applied in order, each to the output of the one before.
+    Pipeline(Vec<Stage>),
+}
+
+impl Curve {
+    /// Checks the numeric parameters of the curve, returning a message describing the first problem.
+    #[allow(clippy::too_many_lines)] // one short arm per curve
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            Curve::Power { gamma } => {
+                if *gamma <= 0.0 {
+                    return Err("Gamma value must be positive".to_string());
+                }
+            }
+            Curve::Piecewise(piecewise) => piecewise.validate()?,
+            Curve::Bt1886 {
+                black_level,
+                white_level,
+            } => {
+                if *black_level < 0.0 {
+                    return Err("black_level must not be negative".to_string());
+                }
+                if white_level <= black_level {
+                    return Err("white_level must be greater than black_level".to_string());
+                }
+            }
+            Curve::Pq { peak_nits } => {
+                if *peak_nits <= 0.0 || *peak_nits > PQ_MAX_NITS {
+                    return Err(format!(
+                        "peak_nits must be positive and at most {PQ_MAX_NITS}"
+                    ));
+                }
+            }
+            Curve::Hlg { system_gamma } => {
+                if *system_gamma <= 0.0 {
+                    return Err("system_gamma must be positive".to_string());
+                }
+            }
+            Curve::Gsdf {
+                min_luminance,
+                max_luminance,
+                ..
+            } => {
+                if *min_luminance < gsdf::MIN_LUMINANCE || *max_luminance > gsdf::MAX_LUMINANCE {
+                    return Err(format!(
+                        "min_luminance and max_luminance must be within the GSDF range of {} to {} cd/m²",
+                        gsdf::MIN_LUMINANCE,
+                        gsdf::MAX_LUMINANCE
+                    ));
+                }
+                if max_luminance <= min_luminance {
+                    return Err("max_luminance must be greater than min_luminance".to_string());
+                }
+            }
+            Curve::ToneMap { white, gamma, .. } => {
+                if *white <= 0.0 {
+                    return Err("white must be positive".to_string());
+                }
+                if gamma.is_some_and(|gamma| gamma <= 0.0) {
+                    return Err("Gamma value must be positive".to_string());
+                }
+            }
+            Curve::Sigmoid {
+                midpoint,
+                steepness,
+                gamma,
+            } => {
+                if !(0.0..=1.0).contains(midpoint) {
+                    return Err("midpoint must be between 0.0 and 1.0".to_string());
+                }
+                if *steepness <= 0.0 {
+                    return Err("steepness must be positive".to_string());
+                }
+                if gamma.is_some_and(|gamma| gamma <= 0.0) {
+                    return Err("Gamma value must be positive".to_string());
+                }
+            }
+            Curve::Bipolar { expo, dead_zone } => {
+                if *expo <= 0.0 {
+                    return Err("expo must be positive".to_string());
+                }
+                if !(0.0..1.0).contains(dead_zone) {
+                    return Err("dead_zone must be at least 0.0 and less than 1.0".to_string());
+                }
+            }
+            Curve::DotGain { dot_gain } => {
+                if dot_gain.abs() >= 0.5 {
+                    return Err("dot_gain must be greater than -0.5 and less than 0.5".to_string());
+                }
+            }
+            Curve::DbTaper { min_db, max_db } => {
+                if min_db >= max_db {
+                    return Err("min_db must be less than max_db".to_string());
+                }
+            }
+            Curve::Inverse(curve) => {
+                curve.validate()?;
+                if curve.has_sign_bit() {
+                    return Err(
+                        "Companding curves cannot be inverted; use decoding instead".to_string()
+                    );
+                }
+            }
+            // Only pipeline stages get here, as whole tables are split per channel first
+            Curve::IccChannels(_) => {
+                return Err(
+                    "The profile has a curve for each color channel; select one with channel: red, green or blue"
+                        .to_string(),
+                );
+            }
+            Curve::Pipeline(stages) => {
+                if stages.is_empty() {
+                    return Err("A pipeline needs at least one stage".to_string());
+                }
+                for (index, stage) in stages.iter().enumerate() {
+                    stage
+                        .validate()
+                        .map_err(|message| format!("Stage {}: {message}", index + 1))?;
+                }
+            }
+            Curve::CieLightness
+            | Curve::Dali
+            | Curve::Dimmer(_)
+            | Curve::Easing(_)
+            | Curve::Expression(_)
+            | Curve::Measured(_)
+            | Curve::Fitted(_)
+            | Curve::Icc(_)
+            | Curve::Cube(_)
+            | Curve::CameraLog(_)
+            | Curve::AcesLog(_)
+            | Curve::Companding { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Checks that the curve can fill a table with `size` entries up to `max_value`, in the
+    /// direction selected by `decoding`.
+    pub(crate) fn validate_size(
+        &self,
+        size: usize,
+        max_value: u64,
+        decoding: bool,
+    ) -> Result<(), String> {
+        if !self.has_sign_bit() {
+            return Ok(());
+        }
+        if !decoding && size != 256 {
+            return Err(format!(
+                "G.711 expansion tables are indexed by an 8-bit code, so size must be 256 (got {size})"
+            ));
+        }
+        if decoding && !size.is_power_of_two() {
+            return Err(format!(
+                "G.711 compression tables are indexed by a two's complement sample, so size must be a power of two (got {size})"
+            ));
+        }
+        if decoding && max_value != 255 {
+            return Err(format!(
+                "G.711 codes are 8 bits, so compression tables need max_value: 255 (got {max_value})"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Evaluates expression and pipeline curves at every input of a table with `size` entries,
+    /// reporting values that are not finite inside the expression that produced them, or at
+    /// `span` for pipeline stages that are not expressions.
+    pub(crate) fn validate_values(&self, size: usize, span: Span) -> syn::Result<()> {
+        #[allow(clippy::cast_precision_loss)]
+        let inputs = (0..size).map(|i| i as f64 / (size - 1) as f64);
+        match self {
+            Curve::Expression(expression) => {
+                for x in inputs {
+                    expression.try_eval(x)?;
+                }
+            }
+            Curve::Pipeline(stages) => {
+                for x in inputs {
+                    pipeline::try_apply(stages, x, span)?;
+                }
+            }
+            Curve::Inverse(curve) => curve.validate_values(size, span)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Checks that an inverted curve is monotonic in the `decoding` direction, so that every
+    /// output has a single input.
+    pub(crate) fn validate_inverse(&self, decoding: bool) -> Result<(), String> {
+        let Curve::Inverse(curve) = self else {
+            return Ok(());
+        };
+        let samples = sample_evenly(|x| curve.eval(x, decoding));
+        if samples.iter().any(|value| !value.is_finite()) {
+            return Err(
+                "The curve cannot be inverted because it is not finite everywhere".to_string(),
+            );
+        }
+        let rising = samples.windows(2).all(|pair| pair[1] >= pair[0]);
+        let falling = samples.windows(2).all(|pair| pair[1] <= pair[0]);
+        if !rising && !falling {
+            return Err("The curve cannot be inverted because it is not monotonic".to_string());
+        }
+        if rising && falling {
+            return Err("The curve cannot be inverted because it is constant".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the files the curve was read from, which the generated table depends on.
+    pub(crate) fn source_files(&self) -> Vec<&Path> {
+        match self {
+            Curve::Measured(measured) => measured.source().into_iter().collect(),
+            Curve::Fitted(fit) => fit.source.as_deref().into_iter().collect(),
+            Curve::Icc(icc) => vec![icc.source()],
+            Curve::Cube(cube) => vec![cube.source()],
+            Curve::Inverse(curve) => curve.source_files(),
+            Curve::Pipeline(stages) => stages
+                .iter()
+                .flat_map(|stage| match stage {
+                    Stage::Curve { curve, .. } => curve.source_files(),
+                    Stage::Affine { .. } => Vec::new(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Splits the curves of an RGB profile read without a `channel` into one curve per channel,
+    /// each inverted if the whole curve is.
+    pub(crate) fn split_channels(&self) -> Option<Vec<(Channel, Curve)>> {
+        match self {
+            Curve::IccChannels(curves) => Some(
+                curves
+                    .iter()
+                    .map(|(channel, icc)| (*channel, Curve::Icc(icc.clone())))
+                    .collect(),
+            ),
+            Curve::Inverse(curve) => Some(
+                curve
+                    .split_channels()?
+                    .into_iter()
+                    .map(|(channel, curve)| (channel, Curve::Inverse(Box::new(curve))))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the power-law fit the curve was built from, if any.
+    pub(crate) fn fit(&self) -> Option<&Fit> {
+        match self {
+            Curve::Fitted(fit) => Some(fit),
+            Curve::Inverse(curve) => curve.fit(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether table indices and outputs are signed samples and sign-magnitude codes,
+    /// as described for [`generate_table_values`](crate::generate_table_values).
+    pub(crate) fn has_sign_bit(&self) -> bool {
+        matches!(self, Curve::Companding { .. })
+    }
+
+    /// Checks that the curve can be generated with `decoding: true`.
+    pub(crate) fn validate_decoding(&self) -> Result<(), String> {
+        match self {
+            Curve::ToneMap { gamma: None, .. } => Err(
+                "Tone-mapping curves cannot be decoded; decoding only selects the direction of the optional gamma stage"
+                    .to_string(),
+            ),
+            Curve::Sigmoid { gamma: None, .. } => Err(
+                "The sigmoid curve cannot be decoded; decoding only selects the direction of the optional gamma stage"
+                    .to_string(),
+            ),
+            Curve::Easing(_) => Err("Easing curves cannot be decoded".to_string()),
+            Curve::Expression(_) => Err("Expression curves cannot be decoded".to_string()),
+            Curve::Icc(icc) => icc.validate_decoding(),
+            Curve::Cube(cube) => cube.validate_decoding(),
+            Curve::Inverse(curve) => curve.validate_decoding(),
+            Curve::Pipeline(_) => Err(
+                "Pipelines cannot be decoded as a whole; set decoding on each stage instead"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates the curve for a normalized input, in the decoding direction if `decoding` is set.
+    #[allow(clippy::too_many_lines)] // one short arm per curve
+    pub(crate) fn eval(&self, x: f64, decoding: bool) -> f64 {
+        match self {
+            Curve::Power { gamma } => {
+                if decoding {
+                    x.powf(1.0 / gamma) // Gamma correction/decoding: input^(1/gamma)
+                } else {
+                    x.powf(*gamma) // Gamma encoding (default): input^gamma
+                }
+            }
+            Curve::Piecewise(piecewise) => {
+                if decoding {
+                    piecewise.linear_to_signal(x)
+                } else {
+                    piecewise.signal_to_linear(x)
+                }
+            }
+            Curve::Bt1886 {
+                black_level,
+                white_level,
+            } => {
+                let (a, b) = bt1886_coefficients(*black_level, *white_level);
+                if decoding {
+                    ((x * white_level / a).powf(1.0 / BT1886_GAMMA) - b).max(0.0)
+                } else {
+                    a * (x + b).max(0.0).powf(BT1886_GAMMA) / white_level
+                }
+            }
+            Curve::Pq { peak_nits } => {
+                let scale = peak_nits / PQ_MAX_NITS;
+                if decoding {
+                    pq_inverse_eotf(x * scale)
+                } else {
+                    (pq_eotf(x) / scale).min(1.0)
+                }
+            }
+            Curve::Hlg { system_gamma } => {
+                if decoding {
+                    hlg_oetf(x.powf(1.0 / system_gamma))
+                } else {
+                    hlg_inverse_oetf(x).powf(*system_gamma)
+                }
+            }
+            Curve::CieLightness => {
+                if decoding {
+                    luminance_to_lightness(x)
+                } else {
+                    lightness_to_luminance(x)
+                }
+            }
+            Curve::Dali => {
+                if decoding {
+                    light_to_arc_level(x) / DALI_MAX_LEVEL
+                } else {
+                    arc_level_to_light(x * DALI_MAX_LEVEL)
+                }
+            }
+            Curve::Dimmer(dimmer) => {
+                if decoding {
+                    dimmer.to_level(x)
+                } else {
+                    dimmer.to_output(x)
+                }
+            }
+            Curve::CameraLog(log) => {
+                let peak = log.to_linear(1.0);
+                if decoding {
+                    log.to_log(x * peak)
+                } else {
+                    // Codes below black decode to negative light, which the table cannot hold
+                    (log.to_linear(x) / peak).max(0.0)
+                }
+            }
+            Curve::AcesLog(log) => {
+                if decoding {
+                    log.to_log(x)
+                } else {
+                    log.to_linear(x)
+                }
+            }
+            Curve::Gsdf {
+                min_luminance,
+                max_luminance,
+                low_index,
+                high_index,
+            } => {
+                let (black, white) = (*min_luminance, *max_luminance);
+                let (low_index, high_index) = (*low_index, *high_index);
+                if decoding {
+                    let target = black + x * (white - black);
+                    let index = gsdf::solve_jnd_index(target, low_index, high_index);
+                    (index - low_index) / (high_index - low_index)
+                } else {
+                    let index = low_index + x * (high_index - low_index);
+                    (gsdf::luminance(index) - black) / (white - black)
+                }
+            }
+            Curve::ToneMap {
+                operator,
+                white,
+                gamma,
+            } => {
+                // The operator always runs forward, decoding only selects the gamma stage direction
+                let display = operator.apply(x * white, *white);
+                match gamma {
+                    Some(gamma) => Curve::Power { gamma: *gamma }.eval(display, decoding),
+                    None => display,
+                }
+            }
+            Curve::Sigmoid {
+                midpoint,
+                steepness,
+                gamma,
+            } => {
+                // Like the tone-mapping curves, decoding only selects the gamma stage direction
+                let contrast = sigmoid(x, *midpoint, *steepness);
+                match gamma {
+                    Some(gamma) => Curve::Power { gamma: *gamma }.eval(contrast, decoding),
+                    None => contrast,
+                }
+            }
+            Curve::Easing(easing) => easing.apply(x),
+            Curve::Expression(expression) => expression.eval(x),
+            Curve::Bipolar { expo, dead_zone } => {
+                // The middle index is the center position, both ends are full deflection
+                let position = 2.0 * x - 1.0;
+                let magnitude = position.abs();
+                if magnitude <= *dead_zone {
+                    return 0.0;
+                }
+                let magnitude = (magnitude - dead_zone) / (1.0 - dead_zone);
+                let output = Curve::Power { gamma: *expo }.eval(magnitude, decoding);
+                output.copysign(position)
+            }
+            Curve::DotGain { dot_gain } => {
+                let n = yule_nielsen_n(*dot_gain);
+                if decoding {
+                    1.0 - (1.0 - x).powf(n)
+                } else {
+                    1.0 - (1.0 - x).powf(1.0 / n)
+                }
+            }
+            Curve::Measured(measured) => {
+                if decoding {
+                    measured.to_input(x)
+                } else {
+                    measured.to_output(x)
+                }
+            }
+            Curve::Fitted(fit) => {
+                if decoding {
+                    fit.to_input(x)
+                } else {
+                    fit.to_output(x)
+                }
+            }
+            Curve::Icc(icc) => {
+                if decoding {
+                    icc.to_device(x)
+                } else {
+                    icc.to_linear(x)
+                }
+            }
+            Curve::Cube(cube) => {
+                if decoding {
+                    cube.unapply(x)
+                } else {
+                    cube.apply(x)
+                }
+            }
+            Curve::DbTaper { min_db, max_db } => {
+                if decoding {
+                    gain_to_taper(x, *min_db, *max_db)
+                } else {
+                    taper_to_gain(x, *min_db, *max_db)
+                }
+            }
+            Curve::Companding { .. } => {
+                unreachable!(
+                    "companding tables are built from whole codes in generate_table_values"
+                )
+            }
+            Curve::IccChannels(_) => {
+                unreachable!(
+                    "RGB profiles are split into a table per channel by generate_gamma_table"
+                )
+            }
+            Curve::Inverse(curve) => curve.invert(x, decoding),
+            Curve::Pipeline(stages) => stages.iter().fold(x, |value, stage| stage.apply(value)),
+        }
+    }
+
+    /// Finds the input at which the curve reaches `y` of the way from its first to its last
+    /// output, by bisection. Assumes the curve is monotonic, as checked by
+    /// [`validate_inverse`](Self::validate_inverse).
+    fn invert(&self, y: f64, decoding: bool) -> f64 {
+        let first = self.eval(0.0, decoding);
+        let last = self.eval(1.0, decoding);
+        let target = first + y * (last - first);
+        let rising = last >= first;
+        let (low, high) = bisect(|x| {
+            let value = self.eval(x, decoding);
+            (rising && value < target) || (!rising && value > target)
+        });
+        f64::midpoint(low, high)
+    }
+}
+
+/// Interpolates linearly between `samples` spaced evenly over `0.0..=1.0`, holding the end
+/// values outside that range.
+fn interpolate_samples(samples: &[f64], x: f64) -> f64 {
+    #[allow(clippy::cast_precision_loss)]
+    let position = x.clamp(0.0, 1.0) * (samples.len() - 1) as f64;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let index = (position as usize).min(samples.len() - 2);
+    #[allow(clippy::cast_precision_loss)]
+    let t = position - index as f64;
+    samples[index] + t * (samples[index + 1] - samples[index])
+}
+
+/// Evaluates `curve` at the `INVERSE_SAMPLES + 1` evenly spaced inputs that monotonicity
+/// checks look at.
+fn sample_evenly(curve: impl Fn(f64) -> f64) -> Vec<f64> {
+    (0..=INVERSE_SAMPLES)
+        .map(|i| curve(f64::from(i) / f64::from(INVERSE_SAMPLES)))
+        .collect()
+}
+
+/// Narrows `0.0..=1.0` down to where `before` changes from true to false, by bisection, and
+/// returns the bracket.
+fn bisect(before: impl Fn(f64) -> bool) -> (f64, f64) {
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..INVERSE_ITERATIONS {
+        let middle = f64::midpoint(low, high);
+        if before(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low, high)
+}
+
+/// Returns whether `curve` never falls over `0.0..=1.0` and ends above where it starts,
+/// sampled as for [`Curve::validate_inverse`].
+fn rises_monotonically(curve: impl Fn(f64) -> f64) -> bool {
+    let samples = sample_evenly(curve);
+    samples.windows(2).all(|pair| pair[1] >= pair[0]) && samples[0] < samples[samples.len() - 1]
+}
+
+/// Finds the lowest input in `0.0..=1.0` at which a rising `curve` reaches `y`, bisecting as
+/// [`Curve::invert`] does. Outputs below the start of the curve give `0.0` and outputs above
+/// its end give `1.0`.
+fn lowest_input_reaching(curve: impl Fn(f64) -> f64, y: f64) -> f64 {
+    if y <= curve(0.0) {
+        return 0.0;
+    }
+    bisect(|x| curve(x) < y).1
+}
+
+/// Reads the file named by `path`, relative to `CARGO_MANIFEST_DIR`, reporting errors at the
+/// literal. Returns the resolved path with the contents.
+fn read_manifest_file(path: &LitStr) -> syn::Result<(PathBuf, Vec<u8>)> {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| Error::new(path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let source = PathBuf::from(manifest_dir).join(path.value());
+    let bytes = std::fs::read(&source).map_err(|error| {
+        Error::new(
+            path.span(),
+            format!("Cannot read {}: {error}", source.display()),
+        )
+    })?;
+    Ok((source, bytes))
+}
+
+/// ST 2084 EOTF: signal to luminance as a fraction of 10000 cd/m².
+fn pq_eotf(signal: f64) -> f64 {
+    let p = signal.powf(1.0 / PQ_M2);
+    ((p - PQ_C1).max(0.0) / (PQ_C2 - PQ_C3 * p)).powf(1.0 / PQ_M1)
+}
+
+/// ST 2084 inverse EOTF: luminance as a fraction of 10000 cd/m² to signal.
+fn pq_inverse_eotf(luminance: f64) -> f64 {
+    let y = luminance.powf(PQ_M1);
+    ((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y)).powf(PQ_M2)
+}
+
+/// HLG OETF: normalized scene light to signal.
+fn hlg_oetf(light: f64) -> f64 {
+    if light <= 1.0 / 12.0 {
+        (3.0 * light).sqrt()
+    } else {
+        HLG_A * (12.0 * light - HLG_B).ln() + HLG_C
+    }
+}
+
+/// HLG inverse OETF: signal to normalized scene light.
+fn hlg_inverse_oetf(signal: f64) -> f64 {
+    if signal <= 0.5 {
+        signal * signal / 3.0
+    } else {
+        (((signal - HLG_C) / HLG_A).exp() + HLG_B) / 12.0
+    }
+}
+
+/// CIE `L*` (scaled to `0.0..=1.0`) to relative luminance `Y`.
+fn lightness_to_luminance(lightness: f64) -> f64 {
+    let l = lightness * 100.0;
+    if l > CIE_KAPPA * CIE_EPSILON {
+        ((l + 16.0) / 116.0).powi(3)
+    } else {
+        l / CIE_KAPPA
+    }
+}
+
+/// Relative luminance `Y` to CIE `L*` scaled to `0.0..=1.0`.
+fn luminance_to_lightness(luminance: f64) -> f64 {
+    let l = if luminance > CIE_EPSILON {
+        116.0 * luminance.cbrt() - 16.0
+    } else {
+        CIE_KAPPA * luminance
+    };
+    l / 100.0
+}
+
+/// DALI arc power level to light output as a fraction of full power.
+///
+/// Level `0` is off; fractional levels between off and level `1` give the minimum light output.
+fn arc_level_to_light(level: f64) -> f64 {
+    if level <= 0.0 {
+        return 0.0;
+    }
+    let level = level.max(1.0);
+    10_f64.powf(3.0 * (level - 1.0) / (DALI_MAX_LEVEL - 1.0) - 3.0)
+}
+
+/// Light output as a fraction of full power to DALI arc power level.
+///
+/// Only zero light gives level `0`; any other output needs at least level `1`.
+fn light_to_arc_level(light: f64) -> f64 {
+    if light <= 0.0 {
+        return 0.0;
+    }
+    (1.0 + (DALI_MAX_LEVEL - 1.0) / 3.0 * (light.log10() + 3.0)).max(1.0)
+}
+
+/// Logistic function centered on `midpoint`, rescaled so that `0.0` and `1.0` map to themselves.
+fn sigmoid(x: f64, midpoint: f64, steepness: f64) -> f64 {
+    let logistic = |x: f64| 1.0 / (1.0 + (-steepness * (x - midpoint)).exp());
+    let (low, high) = (logistic(0.0), logistic(1.0));
+    (logistic(x) - low) / (high - low)
+}
+
+/// Yule-Nielsen `n` for which a 50% dot prints as `0.5 + dot_gain`.
+///
+/// With a solid ink that absorbs all light, Murray-Davies and Yule-Nielsen give a printed dot
+/// area of `1 - (1 - area) ^ n`.
+fn yule_nielsen_n(dot_gain: f64) -> f64 {
+    (0.5 - dot_gain).ln() / 0.5_f64.ln()
+}
+
+/// Taper position to gain relative to `max_db`; position `0.0` mutes instead of stopping at `min_db`.
+fn taper_to_gain(position: f64, min_db: f64, max_db: f64) -> f64 {
+    if position <= 0.0 {
+        return 0.0;
+    }
+    let db = min_db + position * (max_db - min_db);
+    10_f64.powf((db - max_db) / 20.0)
+}
+
+/// Gain relative to `max_db` to taper position; gains below `min_db` give position `0.0`.
+fn gain_to_taper(gain: f64, min_db: f64, max_db: f64) -> f64 {
+    if gain <= 0.0 {
+        return 0.0;
+    }
+    let db = max_db + 20.0 * gain.log10();
+    ((db - min_db) / (max_db - min_db)).max(0.0)
+}
+
+/// Returns the BT.1886 user gain `a` and black lift `b` for the given screen luminances.
+fn bt1886_coefficients(black_level: f64, white_level: f64) -> (f64, f64) {
+    let white_root = white_level.powf(1.0 / BT1886_GAMMA);
+    let black_root = black_level.powf(1.0 / BT1886_GAMMA);
+    let a = (white_root - black_root).powf(BT1886_GAMMA);
+    let b = black_root / (white_root - black_root);
+    (a, b)
+}
+
+/// A two-segment transfer curve, expressed in the default (signal to linear) direction:
+///
+/// ```text
+/// linear = signal / slope                                  if signal <= knee
+/// linear = ((signal + offset) / (1 + offset)) ^ power      otherwise
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Piecewise {
+    /// Signal value at which the curve switches from the linear to the power segment.
+    pub(crate) knee: f64,
+    /// Slope of the linear segment, in signal per unit of linear light.
+    pub(crate) slope: f64,
+    /// Offset applied to the signal before the power segment.
+    pub(crate) offset: f64,
+    /// Exponent of the power segment.
+    pub(crate) power: f64,
+}
+
+impl Piecewise {
+    /// The IEC 61966-2-1 sRGB transfer function.
+    pub(crate) const SRGB: Piecewise = Piecewise {
+        knee: 0.040_45,
+        slope: 12.92,
+        offset: 0.055,
+        power: 2.4,
+    };
+
+    /// The inverse of the ITU-R BT.709 OETF, using the exact `α` and `β` constants.
+    pub(crate) const REC709: Piecewise =
+        Piecewise::video(1.099_296_826_809_44, 0.018_053_968_510_807);
+
+    /// The inverse of the ITU-R BT.2020 OETF for 10-bit systems.
+    pub(crate) const REC2020_10BIT: Piecewise = Piecewise::video(1.099, 0.018);
+
+    /// The inverse of the ITU-R BT.2020 OETF for 12-bit systems.
+    pub(crate) const REC2020_12BIT: Piecewise = Piecewise::video(1.0993, 0.0181);
+
+    /// Builds the BT.709/BT.2020 style curve `V = α L^0.45 - (α - 1)` above `L = β`, `V = 4.5 L` below.
+    const fn video(alpha: f64, beta: f64) -> Piecewise {
+        Piecewise {
+            knee: 4.5 * beta,
+            slope: 4.5,
+            offset: alpha - 1.0,
+            power: 1.0 / 0.45,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.knee) {
+            return Err("knee must be at least 0.0 and less than 1.0".to_string());
+        }
+        if self.slope <= 0.0 {
+            return Err("slope must be positive".to_string());
+        }
+        if self.offset <= -1.0 {
+            return Err("offset must be greater than -1.0".to_string());
+        }
+        if self.power <= 0.0 {
+            return Err("power must be positive".to_string());
+        }
+
+        // BT.2020 publishes rounded constants for 10- and 12-bit systems that only meet to
+        // within 0.3% and 0.003%; they are used as published
+        if *self == Piecewise::REC2020_10BIT || *self == Piecewise::REC2020_12BIT {
+            return Ok(());
+        }
+        let linear_segment = self.knee / self.slope;
+        let power_segment = self.power_segment(self.knee);
+        if (linear_segment - power_segment).abs() > CONTINUITY_TOLERANCE * linear_segment.abs() {
+            return Err(format!(
+                "Piecewise curve is not continuous at the knee ({}): the linear segment gives {linear_segment:.6} but the power segment gives {power_segment:.6}",
+                self.knee
+            ));
+        }
+        Ok(())
+    }
+
+    fn power_segment(&self, signal: f64) -> f64 {
+        ((signal + self.offset) / (1.0 + self.offset)).powf(self.power)
+    }
+
+    /// Encoded signal to linear light.
+    fn signal_to_linear(&self, signal: f64) -> f64 {
+        if signal <= self.knee {
+            signal / self.slope
+        } else {
+            self.power_segment(signal)
+        }
+    }
+
+    /// Linear light to encoded signal.
+    fn linear_to_signal(&self, linear: f64) -> f64 {
+        if linear <= self.knee / self.slope {
+            linear * self.slope
+        } else {
+            (1.0 + self.offset) * linear.powf(1.0 / self.power) - self.offset
+        }
+    }
+}
+
+/// Curve selection and numeric parameters collected while parsing.
+///
+/// Parameters are consumed as the [`Curve`] is built, so anything left over was
+/// given for a curve that does not use it.
+#[derive(Default)]
+pub(crate) struct CurveParams {
+    kind: Option<syn::Ident>,
+    values: Vec<(syn::Ident, f64)>,
+    expression: Option<Expression>,
+    stages: Option<(Span, Vec<Stage>)>,
+    points: Option<Points>,
+    interpolation: Option<syn::Ident>,
+    fit: Option<syn::Ident>,
+    icc_profile: Option<IccProfile>,
+    cube_lut: Option<CubeLut>,
+    channel: Option<syn::Ident>,
+    wire_format: Option<syn::LitBool>,
+}
+
+impl CurveParams {
+    /// Returns whether `name` is a numeric curve parameter.
+    pub(crate) fn is_float_parameter(name: &str) -> bool {
+        FLOAT_PARAMETERS.contains(&name)
+    }
+
+    /// Records the `curve` parameter.
+    pub(crate) fn set_kind(&mut self, kind: syn::Ident) {
+        self.kind = Some(kind);
+    }
+
+    /// Records the `expr` parameter.
+    pub(crate) fn set_expression(&mut self, expression: Expression) {
+        self.expression = Some(expression);
+    }
+
+    /// Records the `stages` parameter, with `span` pointing at its name.
+    pub(crate) fn set_stages(&mut self, span: Span, stages: Vec<Stage>) {
+        self.stages = Some((span, stages));
+    }
+
+    /// Records the `points` parameter.
+    pub(crate) fn set_points(&mut self, points: Points) {
+        self.points = Some(points);
+    }
+
+    /// Records the `interpolation` parameter.
+    pub(crate) fn set_interpolation(&mut self, interpolation: syn::Ident) {
+        self.interpolation = Some(interpolation);
+    }
+
+    /// Records the `icc_profile` parameter.
+    pub(crate) fn set_icc_profile(&mut self, profile: IccProfile) {
+        self.icc_profile = Some(profile);
+    }
+
+    /// Records the `cube_lut` parameter.
+    pub(crate) fn set_cube_lut(&mut self, lut: CubeLut) {
+        self.cube_lut = Some(lut);
+    }
+
+    /// Records the `channel` parameter.
+    pub(crate) fn set_channel(&mut self, channel: syn::Ident) {
+        self.channel = Some(channel);
+    }
+
+    /// Records the `wire_format` parameter.
+    pub(crate) fn set_wire_format(&mut self, wire_format: syn::LitBool) {
+        self.wire_format = Some(wire_format);
+    }
+
+    /// Records the `fit` parameter.
+    pub(crate) fn set_fit(&mut self, fit: syn::Ident) {
+        self.fit = Some(fit);
+    }
+
+    /// Records a numeric curve parameter, replacing any earlier value with the same name.
+    pub(crate) fn set(&mut self, ident: syn::Ident, value: f64) {
+        self.values.retain(|(existing, _)| *existing != ident);
+        self.values.push((ident, value));
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.values.iter().any(|(ident, _)| ident == name)
+    }
+
+    fn take(&mut self, name: &str) -> Option<f64> {
+        let index = self.values.iter().position(|(ident, _)| ident == name)?;
+        Some(self.values.remove(index).1)
+    }
+
+    fn require(&mut self, name: &str, span: Span) -> syn::Result<f64> {
+        self.take(name)
+            .ok_or_else(|| Error::new(span, format!("Missing required parameter: {name}")))
+    }
+
+    /// Builds the curve, using `span` for errors about missing parameters.
+    #[allow(clippy::too_many_lines)] // one short arm per curve
+    pub(crate) fn into_curve(mut self, span: Span) -> syn::Result<Curve> {
+        let kind = self.kind.take();
+        let curve_name = match &kind {
+            Some(kind) => kind.to_string(),
+            None if self.expression.is_some() => "expr".to_string(),
+            None if self.icc_profile.is_some() => "icc".to_string(),
+            None if self.cube_lut.is_some() => "cube".to_string(),
+            None if self.fit.is_some() => "fit".to_string(),
+            None if self.points.is_some() => "measured".to_string(),
+            None if self.stages.is_some()
+                || self.contains("from_gamma")
+                || self.contains("to_gamma") =>
+            {
+                "pipeline".to_string()
+            }
+            // A bare knee/slope/offset/power set selects the piecewise curve
+            None if !self.contains("gamma")
+                && PIECEWISE_PARAMETERS.iter().any(|name| self.contains(name)) =>
+            {
+                "piecewise".to_string()
+            }
+            None => "gamma".to_string(),
+        };
+        let curve = match curve_name.as_str() {
+            "gamma" => Curve::Power {
+                gamma: self.require("gamma", span)?,
+            },
+            "piecewise" => Curve::Piecewise(Piecewise {
+                knee: self.require("knee", span)?,
+                slope: self.require("slope", span)?,
+                offset: self.require("offset", span)?,
+                power: self.require("power", span)?,
+            }),
+            "srgb" => Curve::Piecewise(Piecewise::SRGB),
+            "rec709" => Curve::Piecewise(Piecewise::REC709),
+            "rec2020_10bit" => Curve::Piecewise(Piecewise::REC2020_10BIT),
+            "rec2020_12bit" => Curve::Piecewise(Piecewise::REC2020_12BIT),
+            "bt1886" => Curve::Bt1886 {
+                black_level: self.take("black_level").unwrap_or(0.0),
+                white_level: self.take("white_level").unwrap_or(100.0),
+            },
+            "pq" => Curve::Pq {
+                peak_nits: self.take("peak_nits").unwrap_or(PQ_MAX_NITS),
+            },
+            "hlg" => Curve::Hlg {
+                system_gamma: self.take("system_gamma").unwrap_or(1.0),
+            },
+            "cie_lightness" => Curve::CieLightness,
+            "dali" => Curve::Dali,
+            "sigmoid" => Curve::Sigmoid {
+                midpoint: self.take("midpoint").unwrap_or(0.5),
+                steepness: self.require("steepness", span)?,
+                gamma: self.take("gamma"),
+            },
+            "bipolar" => Curve::Bipolar {
+                expo: self.require("expo", span)?,
+                dead_zone: self.take("dead_zone").unwrap_or(0.0),
+            },
+            "dot_gain" => Curve::DotGain {
+                dot_gain: self.require("dot_gain", span)?,
+            },
+            "expr" => Curve::Expression(
+                self.expression
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: expr"))?,
+            ),
+            "pipeline" => Curve::Pipeline(match self.stages.take() {
+                Some((_, stages)) => stages,
+                None => Stage::transcode(
+                    self.require("from_gamma", span)?,
+                    self.require("to_gamma", span)?,
+                ),
+            }),
+            "measured" => {
+                let points = self
+                    .points
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: points"))?;
+                let interpolation = match self.interpolation.take() {
+                    Some(ident) => {
+                        Interpolation::from_name(&ident.to_string()).ok_or_else(|| {
+                            Error::new(
+                                ident.span(),
+                                format!(
+                                "Unknown interpolation: {ident}. Supported interpolations are: {}",
+                                Interpolation::NAMES.join(", ")
+                            ),
+                            )
+                        })?
+                    }
+                    None => Interpolation::Monotone,
+                };
+                let points_span = points.span;
+                Curve::Measured(
+                    Measured::new(points, interpolation)
+                        .map_err(|message| Error::new(points_span, message))?,
+                )
+            }
+            "fit" => {
+                let fit = self
+                    .fit
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: fit"))?;
+                let model = FitModel::from_name(&fit.to_string()).ok_or_else(|| {
+                    Error::new(
+                        fit.span(),
+                        format!(
+                            "Unknown fit: {fit}. Supported fits are: {}",
+                            FitModel::NAMES.join(", ")
+                        ),
+                    )
+                })?;
+                let points = self
+                    .points
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: points"))?;
+                let points_span = points.span;
+                Curve::Fitted(
+                    Fit::new(points, model).map_err(|message| Error::new(points_span, message))?,
+                )
+            }
+            "icc" => {
+                let profile = self
+                    .icc_profile
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: icc_profile"))?;
+                let profile_span = profile.span;
+                let error = |message| Error::new(profile_span, message);
+                if let Some(channel) = self.take_channel()? {
+                    Curve::Icc(profile.curve(channel).map_err(error)?)
+                } else {
+                    let mut curves = profile.default_curves().map_err(error)?;
+                    if curves.len() == 1 {
+                        Curve::Icc(curves.remove(0).1)
+                    } else {
+                        Curve::IccChannels(curves)
+                    }
+                }
+            }
+            "cube" => {
+                let lut = self
+                    .cube_lut
+                    .take()
+                    .ok_or_else(|| Error::new(span, "Missing required parameter: cube_lut"))?;
+                let lut_span = lut.span;
+                let channel = self.take_channel()?;
+                Curve::Cube(
+                    lut.curve(channel)
+                        .map_err(|message| Error::new(lut_span, message))?,
+                )
+            }
+            "db_taper" => Curve::DbTaper {
+                min_db: self.take("min_db").unwrap_or(-60.0),
+                max_db: self.take("max_db").unwrap_or(0.0),
+            },
+            "gsdf" => {
+                let min_luminance = self.require("min_luminance", span)?;
+                let max_luminance = self.require("max_luminance", span)?;
+                Curve::Gsdf {
+                    min_luminance,
+                    max_luminance,
+                    low_index: gsdf::jnd_index(min_luminance),
+                    high_index: gsdf::jnd_index(max_luminance),
+                }
+            }
+            "hable" => Curve::ToneMap {
+                operator: ToneMap::Hable,
+                white: self.take("white").unwrap_or(tonemap::HABLE_WHITE),
+                gamma: self.take("gamma"),
+            },
+            name => {
+                if let Some(dimmer) = Dimmer::from_name(name) {
+                    Curve::Dimmer(dimmer)
+                } else if let Some(log) = CameraLog::from_name(name) {
+                    Curve::CameraLog(log)
+                } else if let Some(log) = AcesLog::from_name(name) {
+                    Curve::AcesLog(log)
+                } else if let Some(easing) = Easing::from_name(name) {
+                    Curve::Easing(easing)
+                } else if let Some(law) = Companding::from_name(name) {
+                    Curve::Companding {
+                        law,
+                        wire_format: self.wire_format.take().is_none_or(|value| value.value),
+                    }
+                } else if let Some(operator) = ToneMap::from_name(name) {
+                    Curve::ToneMap {
+                        operator,
+                        white: self.require("white", span)?,
+                        gamma: self.take("gamma"),
+                    }
+                } else {
+                    return Err(Error::new(
+                        kind.as_ref().map_or(span, syn::Ident::span),
+                        format!(
+                            "Unknown curve: {curve_name}. Supported curves are: {}, {}, {}, {}, {}, {}, {}",
+                            CURVE_NAMES.join(", "),
+                            Dimmer::NAMES.join(", "),
+                            CameraLog::NAMES.join(", "),
+                            AcesLog::NAMES.join(", "),
+                            ToneMap::NAMES.join(", "),
+                            Easing::NAMES.join(", "),
+                            Companding::NAMES.join(", ")
+                        ),
+                    ));
+                }
+            }
+        };
+
+        self.check_unused(&format!("curve {curve_name}"))?;
+        Ok(curve)
+    }
+
+    /// Consumes the `channel` parameter, if given.
+    fn take_channel(&mut self) -> syn::Result<Option<Channel>> {
+        let Some(ident) = self.channel.take() else {
+            return Ok(None);
+        };
+        let channel = Channel::from_name(&ident.to_string()).ok_or_else(|| {
+            Error::new(
+                ident.span(),
+                format!(
+                    "Unknown channel: {ident}. Supported channels are: {}",
+                    Channel::NAMES.join(", ")
+                ),
+            )
+        })?;
+        Ok(Some(channel))
+    }
+
+    /// Builds a pipeline stage: a gain stage if `gain` is given, otherwise a curve stage using
+    /// the same parameters as a whole table, with `span` for errors about missing parameters.
+    pub(crate) fn into_stage(
+        mut self,
+        gain: Option<f64>,
+        decoding: Option<bool>,
+        invert: bool,
+        span: Span,
+    ) -> syn::Result<Stage> {
+        let Some(gain) = gain else {
+            let curve = self.into_curve(span)?;
+            return Ok(Stage::Curve {
+                curve: if invert {
+                    Curve::Inverse(Box::new(curve))
+                } else {
+                    curve
+                },
+                decoding: decoding.unwrap_or(false),
+            });
+        };
+        if decoding.is_some() || invert {
+            return Err(Error::new(
+                span,
+                "Gain stages have no decoding direction and cannot be inverted",
+            ));
+        }
+        let offset = self.take("offset").unwrap_or(0.0);
+        if let Some(kind) = &self.kind {
+            return Err(Error::new(
+                kind.span(),
+                "Parameter curve is not used by a gain stage",
+            ));
+        }
+        self.check_unused("a gain stage")?;
+        Ok(Stage::Affine { gain, offset })
+    }
+
+    /// Reports the first parameter that was not consumed by `user`.
+    fn check_unused(&self, user: &str) -> syn::Result<()> {
+        if let Some((span, _)) = &self.stages {
+            return Err(Error::new(
+                *span,
+                format!("Parameter stages is not used by {user}"),
+            ));
+        }
+        if let Some(points) = &self.points {
+            return Err(Error::new(
+                points.span,
+                format!("Parameter points is not used by {user}"),
+            ));
+        }
+        if let Some(profile) = &self.icc_profile {
+            return Err(Error::new(
+                profile.span,
+                format!("Parameter icc_profile is not used by {user}"),
+            ));
+        }
+        if let Some(lut) = &self.cube_lut {
+            return Err(Error::new(
+                lut.span,
+                format!("Parameter cube_lut is not used by {user}"),
+            ));
+        }
+        if let Some(channel) = &self.channel {
+            return Err(Error::new(
+                channel.span(),
+                format!("Parameter channel is not used by {user}"),
+            ));
+        }
+        if let Some(wire_format) = &self.wire_format {
+            return Err(Error::new(
+                wire_format.span(),
+                format!("Parameter wire_format is not used by {user}"),
+            ));
+        }
+        if let Some(fit) = &self.fit {
+            return Err(Error::new(
+                fit.span(),
+                format!("Parameter fit is not used by {user}"),
+            ));
+        }
+        if let Some(interpolation) = &self.interpolation {
+            return Err(Error::new(
+                interpolation.span(),
+                format!("Parameter interpolation is not used by {user}"),
+            ));
+        }
+        if let Some(expression) = &self.expression {
+            return Err(Error::new(
+                expression.span(),
+                format!("Parameter expr is not used by {user}"),
+            ));
+        }
+        if let Some((ident, _)) = self.values.first() {
+            return Err(Error::new(
+                ident.span(),
+                format!("Parameter {ident} is not used by {user}"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRGB: Curve = Curve::Piecewise(Piecewise::SRGB);
+
+    fn params(kind: Option<&str>, values: &[(&str, f64)]) -> CurveParams {
+        let mut params = CurveParams::default();
+        if let Some(kind) = kind {
+            params.set_kind(syn::parse_str(kind).unwrap());
+        }
+        for (name, value) in values {
+            params.set(syn::parse_str(name).unwrap(), *value);
+        }
+        params
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        for i in 0..=100 {
+            let x = f64::from(i) / 100.0;
+            let round_trip = SRGB.eval(SRGB.eval(x, false), true);
+            assert!((round_trip - x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_srgb_known_values() {
+        // Mid-grey and the linear segment from IEC 61966-2-1
+        assert!((SRGB.eval(0.5, false) - 0.214_041).abs() < 1e-6);
+        assert!((SRGB.eval(0.02, false) - 0.02 / 12.92).abs() < 1e-12);
+        assert!((SRGB.eval(0.001, true) - 0.012_92).abs() < 1e-12);
+        assert!((SRGB.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!((SRGB.eval(1.0, true) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_piecewise_matches_srgb() {
+        // sRGB expressed through the generic piecewise parameters
+        let curve = params(
+            None,
+            &[
+                ("knee", 0.040_45),
+                ("slope", 12.92),
+                ("offset", 0.055),
+                ("power", 2.4),
+            ],
+        )
+        .into_curve(Span::call_site())
+        .unwrap();
+        assert_eq!(curve, SRGB);
+        assert!(curve.validate().is_ok());
+    }
+
+    #[test]
+    fn test_piecewise_rec709_constants_are_continuous() {
+        // Rec.709 with its exact constants rounded to nine places
+        let curve = Curve::Piecewise(Piecewise {
+            knee: 0.081_242_858,
+            slope: 4.5,
+            offset: 0.099_296_827,
+            power: 1.0 / 0.45,
+        });
+        assert!(curve.validate().is_ok());
+        assert!((curve.eval(curve.eval(0.5, true), false) - 0.5).abs() < 1e-12);
+
+        // The commonly quoted 0.081 and 0.099 leave a gap of 5.5e-5 at the knee
+        let rounded = Curve::Piecewise(Piecewise {
+            knee: 0.081,
+            slope: 4.5,
+            offset: 0.099,
+            power: 1.0 / 0.45,
+        });
+        assert!(rounded.validate().is_err());
+    }
+
+    #[test]
+    fn test_piecewise_small_gap() {
+        // sRGB with the linear segment 1e-4 off at the knee
+        let curve = Curve::Piecewise(Piecewise {
+            slope: 12.92 / (1.0 + 1e-4),
+            ..Piecewise::SRGB
+        });
+        assert!(curve
+            .validate()
+            .unwrap_err()
+            .contains("not continuous at the knee"));
+    }
+
+    #[test]
+    fn test_video_presets_are_continuous() {
+        for preset in [
+            Piecewise::SRGB,
+            Piecewise::REC709,
+            Piecewise::REC2020_10BIT,
+            Piecewise::REC2020_12BIT,
+        ] {
+            assert!(Curve::Piecewise(preset).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rec709_oetf_known_values() {
+        let rec709 = Curve::Piecewise(Piecewise::REC709);
+        // OETF: 18% grey encodes to about 0.409, the linear segment is 4.5 L
+        assert!((rec709.eval(0.18, true) - 0.408_848).abs() < 1e-6);
+        assert!((rec709.eval(0.01, true) - 0.045).abs() < 1e-12);
+        assert!((rec709.eval(1.0, true) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bt1886_black_level() {
+        let curve = Curve::Bt1886 {
+            black_level: 0.1,
+            white_level: 100.0,
+        };
+        assert!(curve.validate().is_ok());
+        // Black and white signal reproduce the screen luminances
+        assert!((curve.eval(0.0, false) - 0.001).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!((curve.eval(curve.eval(0.5, false), true) - 0.5).abs() < 1e-12);
+
+        // Without a black level BT.1886 is a pure 2.4 power law
+        let pure = Curve::Bt1886 {
+            black_level: 0.0,
+            white_level: 100.0,
+        };
+        assert!((pure.eval(0.5, false) - 0.5_f64.powf(2.4)).abs() < 1e-12);
+
+        let inverted = Curve::Bt1886 {
+            black_level: 100.0,
+            white_level: 0.1,
+        };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn test_pq_known_values() {
+        let full_range = Curve::Pq {
+            peak_nits: PQ_MAX_NITS,
+        };
+        // 100 cd/m² encodes to a PQ signal of about 0.508
+        assert!((full_range.eval(0.01, true) - 0.508_078).abs() < 1e-6);
+        assert!((full_range.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!((full_range.eval(full_range.eval(0.3, false), true) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_pq_peak_nits() {
+        let curve = Curve::Pq { peak_nits: 1000.0 };
+        assert!(curve.validate().is_ok());
+        // The 1000 cd/m² signal reaches full scale, brighter signals clip
+        let peak_signal = curve.eval(1.0, true);
+        assert!((peak_signal - 0.751_827).abs() < 1e-6);
+        assert!((curve.eval(peak_signal, false) - 1.0).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+
+        assert!(Curve::Pq { peak_nits: 0.0 }.validate().is_err());
+        assert!(Curve::Pq {
+            peak_nits: 20_000.0
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_hlg_known_values() {
+        let scene = Curve::Hlg { system_gamma: 1.0 };
+        // The segments meet at 1/12 scene light, signal 0.5; reference white 0.26496 is 75%
+        assert!((scene.eval(1.0 / 12.0, true) - 0.5).abs() < 1e-12);
+        assert!((scene.eval(0.264_96, true) - 0.75).abs() < 1e-4);
+        assert!((scene.eval(1.0, true) - 1.0).abs() < 1e-6);
+        for i in 0..=20 {
+            let x = f64::from(i) / 20.0;
+            assert!((scene.eval(scene.eval(x, false), true) - x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_hlg_system_gamma() {
+        let display = Curve::Hlg { system_gamma: 1.2 };
+        let scene = Curve::Hlg { system_gamma: 1.0 };
+        assert!(display.validate().is_ok());
+        assert!((display.eval(0.75, false) - scene.eval(0.75, false).powf(1.2)).abs() < 1e-12);
+        assert!((display.eval(display.eval(0.4, false), true) - 0.4).abs() < 1e-12);
+        assert!(Curve::Hlg { system_gamma: 0.0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_cie_lightness_known_values() {
+        let curve = Curve::CieLightness;
+        // L* = 50 is 18.42% luminance; the segments meet at L* = 8
+        assert!((curve.eval(0.5, false) - 0.184_187).abs() < 1e-6);
+        assert!((curve.eval(0.08, false) - CIE_EPSILON).abs() < 1e-12);
+        assert!((curve.eval(0.04, false) - 4.0 / CIE_KAPPA).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        for i in 0..=50 {
+            let x = f64::from(i) / 50.0;
+            assert!((curve.eval(curve.eval(x, false), true) - x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_dali_known_values() {
+        let curve = Curve::Dali;
+        let level = |n: f64| n / DALI_MAX_LEVEL;
+        // Level 0 is off, level 1 is 0.1%, level 85 is 1%, level 254 is 100%
+        assert!(curve.eval(0.0, false).abs() < 1e-12);
+        assert!((curve.eval(level(1.0), false) - 0.001).abs() < 1e-12);
+        assert!((curve.eval(level(85.333_333_333_333_33), false) - 0.01).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        // Between off and level 1 the light stays at the minimum
+        assert!((curve.eval(level(0.5), false) - 0.001).abs() < 1e-12);
+        for n in 1..=254 {
+            let x = level(f64::from(n));
+            assert!((curve.eval(curve.eval(x, false), true) - x).abs() < 1e-12);
+        }
+        // Any light at all needs level 1
+        assert!(curve.eval(0.0, true).abs() < 1e-12);
+        assert!((curve.eval(1e-6, true) - level(1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_camera_log_normalization() {
+        for name in CameraLog::NAMES {
+            let curve = params(Some(name), &[])
+                .into_curve(Span::call_site())
+                .unwrap();
+            // Code 1.0 maps to full scale and back, codes below black clamp to 0
+            assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12, "{name}");
+            assert!((curve.eval(1.0, true) - 1.0).abs() < 1e-12, "{name}");
+            assert!(curve.eval(0.0, false) >= 0.0, "{name}");
+            assert!(
+                (curve.eval(curve.eval(0.6, false), true) - 0.6).abs() < 1e-9,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gsdf_calibration() {
+        let gsdf = |min_luminance, max_luminance| {
+            params(
+                Some("gsdf"),
+                &[
+                    ("min_luminance", min_luminance),
+                    ("max_luminance", max_luminance),
+                ],
+            )
+            .into_curve(Span::call_site())
+            .unwrap()
+        };
+        let curve = gsdf(0.5, 350.0);
+        assert!(curve.validate().is_ok());
+        assert!(curve.eval(0.0, false).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!((curve.eval(curve.eval(0.5, false), true) - 0.5).abs() < 1e-9);
+
+        assert!(gsdf(0.01, 350.0).validate().is_err());
+        assert!(gsdf(350.0, 0.5).validate().is_err());
+    }
+
+    #[test]
+    fn test_tone_map_gamma_stage() {
+        let curve = params(Some("reinhard"), &[("white", 4.0), ("gamma", 2.2)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        let display = ToneMap::Reinhard.apply(2.0, 4.0);
+        assert!((curve.eval(0.5, true) - display.powf(1.0 / 2.2)).abs() < 1e-12);
+        assert!((curve.eval(0.5, false) - display.powf(2.2)).abs() < 1e-12);
+        assert!((curve.eval(1.0, true) - 1.0).abs() < 1e-12);
+        assert!(curve.validate_decoding().is_ok());
+
+        let curve = params(Some("hable"), &[])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!(curve.validate_decoding().is_err());
+
+        assert!(params(Some("aces_fitted"), &[])
+            .into_curve(Span::call_site())
+            .is_err());
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        let curve = params(Some("sigmoid"), &[("steepness", 10.0)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.validate().is_ok());
+        assert!(curve.eval(0.0, false).abs() < 1e-12);
+        assert!((curve.eval(0.5, false) - 0.5).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        // Contrast is raised around the midpoint: 0.25 darkens to 0.0701
+        assert!((curve.eval(0.25, false) - 0.070_1).abs() < 1e-4);
+        assert!(curve.validate_decoding().is_err());
+
+        // An off-center midpoint with a gamma encoding stage
+        let curve = params(
+            Some("sigmoid"),
+            &[("steepness", 8.0), ("midpoint", 0.3), ("gamma", 2.2)],
+        )
+        .into_curve(Span::call_site())
+        .unwrap();
+        let contrast = sigmoid(0.3, 0.3, 8.0);
+        assert!(contrast > 0.0 && contrast < 0.5);
+        assert!((curve.eval(0.3, true) - contrast.powf(1.0 / 2.2)).abs() < 1e-12);
+        assert!((curve.eval(1.0, true) - 1.0).abs() < 1e-12);
+        assert!(curve.validate_decoding().is_ok());
+
+        assert!(params(Some("sigmoid"), &[("steepness", 0.0)])
+            .into_curve(Span::call_site())
+            .unwrap()
+            .validate()
+            .is_err());
+        assert!(params(Some("sigmoid"), &[])
+            .into_curve(Span::call_site())
+            .is_err());
+    }
+
+    #[test]
+    fn test_bipolar() {
+        let curve = params(Some("bipolar"), &[("expo", 2.0), ("dead_zone", 0.2)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.validate().is_ok());
+        // Full deflection either way, nothing inside the dead zone
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        assert!((curve.eval(0.0, false) + 1.0).abs() < 1e-12);
+        assert!(curve.eval(0.5, false).abs() < 1e-12);
+        assert!(curve.eval(0.6, false).abs() < 1e-12);
+        assert!(curve.eval(0.4, true).abs() < 1e-12);
+        // Halfway between the dead zone and the end is a quarter, or 0.707 when decoding
+        assert!((curve.eval(0.8, false) - 0.25).abs() < 1e-12);
+        assert!((curve.eval(0.2, false) + 0.25).abs() < 1e-12);
+        assert!((curve.eval(0.8, true) - 0.5_f64.sqrt()).abs() < 1e-12);
+
+        for (expo, dead_zone) in [(0.0, 0.0), (2.0, -0.1), (2.0, 1.0)] {
+            let curve = Curve::Bipolar { expo, dead_zone };
+            assert!(curve.validate().is_err(), "{expo} {dead_zone}");
+        }
+        assert!(params(Some("bipolar"), &[])
+            .into_curve(Span::call_site())
+            .is_err());
+    }
+
+    #[test]
+    fn test_dot_gain() {
+        let curve = params(Some("dot_gain"), &[("dot_gain", 0.18)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.validate().is_ok());
+        // A 50% dot prints as 68%, so a 34.4% dot is needed to print 50%
+        assert!((curve.eval(0.5, true) - 0.68).abs() < 1e-12);
+        assert!((curve.eval(0.5, false) - 0.344_042).abs() < 1e-6);
+        assert!((curve.eval(curve.eval(0.5, false), true) - 0.5).abs() < 1e-12);
+        for x in [0.0, 1.0] {
+            assert!((curve.eval(x, false) - x).abs() < 1e-12);
+            assert!((curve.eval(x, true) - x).abs() < 1e-12);
+        }
+
+        // No gain leaves the tones alone
+        let curve = Curve::DotGain { dot_gain: 0.0 };
+        assert!((curve.eval(0.3, false) - 0.3).abs() < 1e-12);
+
+        assert!(Curve::DotGain { dot_gain: 0.5 }.validate().is_err());
+        assert!(Curve::DotGain { dot_gain: -0.1 }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_expression() {
+        let expression = Expression::new(&syn::parse_str("x.powf(2.0)").unwrap()).unwrap();
+        let mut curve_params = params(None, &[]);
+        curve_params.set_expression(expression.clone());
+        let curve = curve_params.into_curve(Span::call_site()).unwrap();
+        assert_eq!(curve, Curve::Expression(expression.clone()));
+        assert!((curve.eval(0.5, false) - 0.25).abs() < 1e-12);
+        assert!(curve.validate_decoding().is_err());
+        assert!(curve.validate_values(256, Span::call_site()).is_ok());
+
+        // An expression does not combine with another curve or its parameters
+        let mut curve_params = params(Some("srgb"), &[]);
+        curve_params.set_expression(expression.clone());
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+        let mut curve_params = params(None, &[("gamma", 2.2)]);
+        curve_params.set_expression(expression);
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+        assert!(params(Some("expr"), &[])
+            .into_curve(Span::call_site())
+            .is_err());
+
+        let expression = Expression::new(&syn::parse_str("x.ln()").unwrap()).unwrap();
+        assert!(Curve::Expression(expression)
+            .validate_values(10, Span::call_site())
+            .is_err());
+    }
+
+    #[test]
+    fn test_inverse() {
+        // The inverse of a power law is the power law with 1/gamma
+        let curve = Curve::Inverse(Box::new(Curve::Power { gamma: 2.2 }));
+        assert!(curve.validate().is_ok());
+        assert!(curve.validate_inverse(false).is_ok());
+        for i in 0..=20 {
+            let x = f64::from(i) / 20.0;
+            assert!((curve.eval(x, false) - x.powf(1.0 / 2.2)).abs() < 1e-12);
+            assert!((curve.eval(x, true) - x.powf(2.2)).abs() < 1e-12);
+        }
+
+        // A sigmoid has no closed-form decoding, but can be inverted
+        let sigmoid = params(Some("sigmoid"), &[("steepness", 10.0)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        let curve = Curve::Inverse(Box::new(sigmoid.clone()));
+        assert!(curve.validate_inverse(false).is_ok());
+        assert!((sigmoid.eval(curve.eval(0.3, false), false) - 0.3).abs() < 1e-12);
+
+        // Falling curves invert over their output range, from the first entry to the last
+        let expression = Expression::new(&syn::parse_str("2.0 - x * x").unwrap()).unwrap();
+        let curve = Curve::Inverse(Box::new(Curve::Expression(expression)));
+        assert!(curve.validate_inverse(false).is_ok());
+        assert!((curve.eval(0.25, false) - 0.5).abs() < 1e-12);
+
+        let bounce = Curve::Inverse(Box::new(
+            params(Some("ease_out_bounce"), &[])
+                .into_curve(Span::call_site())
+                .unwrap(),
+        ));
+        assert!(bounce.validate_inverse(false).is_err());
+        let flat = Expression::new(&syn::parse_str("0.5").unwrap()).unwrap();
+        assert!(Curve::Inverse(Box::new(Curve::Expression(flat)))
+            .validate_inverse(false)
+            .is_err());
+        let law = params(Some("mu_law"), &[])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(Curve::Inverse(Box::new(law)).validate().is_err());
+    }
+
+    #[test]
+    fn test_pipeline() {
+        // from_gamma/to_gamma transcodes between two power laws
+        let curve = params(None, &[("from_gamma", 2.2), ("to_gamma", 2.5)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert_eq!(curve, Curve::Pipeline(Stage::transcode(2.2, 2.5)));
+        assert!(curve.validate().is_ok());
+        assert!((curve.eval(0.5, false) - 0.5_f64.powf(2.2 / 2.5)).abs() < 1e-12);
+        assert!(curve.validate_decoding().is_err());
+        assert!(params(None, &[("from_gamma", 2.2)])
+            .into_curve(Span::call_site())
+            .is_err());
+
+        // sRGB to linear, 80% gain, then encoded with gamma 2.5
+        let mut curve_params = params(None, &[]);
+        curve_params.set_stages(
+            Span::call_site(),
+            vec![
+                Stage::Curve {
+                    curve: SRGB,
+                    decoding: false,
+                },
+                Stage::Affine {
+                    gain: 0.8,
+                    offset: 0.0,
+                },
+                Stage::Curve {
+                    curve: Curve::Power { gamma: 2.5 },
+                    decoding: true,
+                },
+            ],
+        );
+        let curve = curve_params.into_curve(Span::call_site()).unwrap();
+        assert!(curve.validate().is_ok());
+        let expected = (SRGB.eval(0.5, false) * 0.8).powf(1.0 / 2.5);
+        assert!((curve.eval(0.5, false) - expected).abs() < 1e-12);
+
+        // Stage errors name the stage
+        let curve = Curve::Pipeline(vec![Stage::Curve {
+            curve: Curve::Power { gamma: -1.0 },
+            decoding: false,
+        }]);
+        assert_eq!(
+            curve.validate().unwrap_err(),
+            "Stage 1: Gamma value must be positive"
+        );
+        assert!(Curve::Pipeline(Vec::new()).validate().is_err());
+
+        let mut curve_params = params(Some("srgb"), &[]);
+        curve_params.set_stages(Span::call_site(), Stage::transcode(2.2, 2.5));
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+    }
+
+    #[test]
+    fn test_measured() {
+        let points = |values: &[(f64, f64)]| Points {
+            span: Span::call_site(),
+            values: values.to_vec(),
+            source: None,
+        };
+
+        // points alone select the measured curve, normalized to the first and last point
+        let mut curve_params = params(None, &[]);
+        curve_params.set_points(points(&[(0.0, 1.0), (0.5, 2.0), (1.0, 5.0)]));
+        curve_params.set_interpolation(syn::parse_str("linear").unwrap());
+        let curve = curve_params.into_curve(Span::call_site()).unwrap();
+        assert!((curve.eval(0.5, false) - 0.25).abs() < 1e-12);
+        assert!((curve.eval(0.25, true) - 0.5).abs() < 1e-12);
+        assert!(curve.source_files().is_empty());
+
+        let mut curve_params = params(None, &[]);
+        curve_params.set_points(points(&[(0.0, 0.0), (1.0, 1.0)]));
+        curve_params.set_interpolation(syn::parse_str("cubic").unwrap());
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+
+        // Decreasing outputs and parameters for other curves are rejected
+        let mut curve_params = params(None, &[]);
+        curve_params.set_points(points(&[(0.0, 0.0), (0.5, 0.8), (1.0, 0.6)]));
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+        let mut curve_params = params(Some("srgb"), &[]);
+        curve_params.set_interpolation(syn::parse_str("linear").unwrap());
+        assert!(curve_params.into_curve(Span::call_site()).is_err());
+    }
+
+    #[test]
+    fn test_into_stage() {
+        let stage = params(None, &[("offset", 0.1)])
+            .into_stage(Some(0.5), None, false, Span::call_site())
+            .unwrap();
+        assert_eq!(
+            stage,
+            Stage::Affine {
+                gain: 0.5,
+                offset: 0.1
+            }
+        );
+
+        // Without gain, offset belongs to the piecewise curve
+        assert!(params(None, &[("offset", 0.1)])
+            .into_stage(None, None, false, Span::call_site())
+            .is_err());
+        assert!(params(Some("srgb"), &[])
+            .into_stage(Some(0.5), None, false, Span::call_site())
+            .is_err());
+        assert!(params(None, &[])
+            .into_stage(Some(0.5), Some(true), false, Span::call_site())
+            .is_err());
+
+        let stage = params(None, &[("gamma", 2.2)])
+            .into_stage(None, Some(true), true, Span::call_site())
+            .unwrap();
+        assert_eq!(
+            stage,
+            Stage::Curve {
+                curve: Curve::Inverse(Box::new(Curve::Power { gamma: 2.2 })),
+                decoding: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_db_taper() {
+        let curve = params(Some("db_taper"), &[])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.validate().is_ok());
+        assert!(curve.eval(0.0, false).abs() < 1e-12);
+        assert!((curve.eval(1.0, false) - 1.0).abs() < 1e-12);
+        // Halfway down a -60..0 dB taper is -30 dB
+        assert!((curve.eval(0.5, false) - 0.031_623).abs() < 1e-6);
+        assert!((curve.eval(curve.eval(0.25, false), true) - 0.25).abs() < 1e-12);
+        // Gains below the floor decode to the bottom of the taper
+        assert!(curve.eval(1e-4, true).abs() < 1e-12);
+
+        let curve = params(Some("db_taper"), &[("min_db", -20.0), ("max_db", -40.0)])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.validate().is_err());
+    }
+
+    #[test]
+    fn test_companding_size() {
+        let curve = params(Some("a_law"), &[])
+            .into_curve(Span::call_site())
+            .unwrap();
+        assert!(curve.has_sign_bit());
+        assert!(curve.validate_size(256, 32767, false).is_ok());
+        assert!(curve.validate_size(255, 32767, false).is_err());
+        assert!(curve.validate_size(8192, 255, true).is_ok());
+        assert!(curve.validate_size(8000, 255, true).is_err());
+        assert!(curve.validate_size(8192, 127, true).is_err());
+        assert!(SRGB.validate_size(255, 254, false).is_ok());
+    }
+
+    #[test]
+    fn test_piecewise_discontinuity() {
+        let curve = Curve::Piecewise(Piecewise {
+            knee: 0.1,
+            slope: 2.0,
+            offset: 0.055,
+            power: 2.4,
+        });
+        let result = curve.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("not continuous at the knee"));
+    }
+
+    #[test]
+    fn test_piecewise_missing_parameter() {
+        let result = params(None, &[("knee", 0.081), ("slope", 4.5), ("power", 2.2)])
+            .into_curve(Span::call_site());
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Missing required parameter: offset"));
+    }
+
+    #[test]
+    fn test_curve_params_unused_parameter() {
+        let result = params(Some("srgb"), &[("gamma", 2.2)]).into_curve(Span::call_site());
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Parameter gamma is not used by curve srgb"));
+    }
+
+    #[test]
+    fn test_curve_params_unknown_curve() {
+        let result = params(Some("rec601"), &[]).into_curve(Span::call_site());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Unknown curve"));
+    }
+}
@@ -0,0 +1,219 @@
+//! ITU-T G.711 companding for audio.
+//!
+//! Samples are coded in eight segments of sixteen steps each, the step size doubling from one
+//! segment to the next. μ-law works on 14-bit samples and A-law on 13-bit samples. Codes are
+//! handled as a sign bit (set for negative samples), a 3-bit segment and a 4-bit step. On the
+//! wire, G.711 inverts all bits of μ-law codes and the even bits of A-law codes.
+
+/// Bias added to μ-law magnitudes so that every segment boundary is a power of two.
+const MU_LAW_BIAS: i32 = 33;
+/// Largest μ-law magnitude before the bias is added.
+const MU_LAW_CLIP: i32 = 8159;
+
+/// A companding law selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Companding {
+    /// G.711 μ-law, used in North America and Japan.
+    MuLaw,
+    /// G.711 A-law, used in Europe.
+    ALaw,
+}
+
+impl Companding {
+    /// Names accepted by the `curve` parameter, in the same order as [`Companding::from_name`].
+    pub(crate) const NAMES: [&'static str; 2] = ["mu_law", "a_law"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Companding> {
+        match name {
+            "mu_law" => Some(Companding::MuLaw),
+            "a_law" => Some(Companding::ALaw),
+            _ => None,
+        }
+    }
+
+    /// Bits of a linear sample: 14 for μ-law, 13 for A-law.
+    pub(crate) fn sample_bits(self) -> u32 {
+        match self {
+            Companding::MuLaw => 14,
+            Companding::ALaw => 13,
+        }
+    }
+
+    /// The bits G.711 inverts on the wire.
+    fn wire_mask(self) -> u8 {
+        match self {
+            Companding::MuLaw => 0xFF,
+            Companding::ALaw => 0xD5,
+        }
+    }
+
+    /// Encodes a sample of [`sample_bits`](Self::sample_bits) bits. Codes are on the wire
+    /// when `wire_format` is set, and sign, segment and step otherwise.
+    pub(crate) fn encode(self, sample: i32, wire_format: bool) -> u8 {
+        let negative = sample < 0;
+        let (segment, step) = match self {
+            Companding::MuLaw => {
+                let magnitude = sample.unsigned_abs().min(MU_LAW_CLIP.unsigned_abs());
+                // 33..=8192, where segment n holds 64 << n up to 128 << n
+                let biased = magnitude + MU_LAW_BIAS.unsigned_abs();
+                let segment = (biased.ilog2() - 5).min(7);
+                let step = if segment == 7 && biased >= 1 << 13 {
+                    15
+                } else {
+                    (biased >> (segment + 1)) & 0xF
+                };
+                (segment, step)
+            }
+            Companding::ALaw => {
+                // Negative samples are one's complement, so -1 codes like 0
+                let magnitude = if negative { -sample - 1 } else { sample };
+                let magnitude = magnitude.unsigned_abs().min(4095);
+                // Segments 0 and 1 both have a step of 2, segment n > 1 a step of 1 << n
+                let segment = magnitude.max(16).ilog2() - 4;
+                let step = (magnitude >> segment.max(1)) & 0xF;
+                (segment, step)
+            }
+        };
+        let sign = if negative { 0x80 } else { 0 };
+        // Both fit in the low seven bits
+        #[allow(clippy::cast_possible_truncation)]
+        let code = sign | (segment << 4) as u8 | step as u8;
+        if wire_format {
+            code ^ self.wire_mask()
+        } else {
+            code
+        }
+    }
+
+    /// Decodes `code` to a sample of [`sample_bits`](Self::sample_bits) bits, reading it in
+    /// the same format as [`encode`](Self::encode) writes it.
+    pub(crate) fn decode(self, code: u8, wire_format: bool) -> i32 {
+        let code = if wire_format {
+            code ^ self.wire_mask()
+        } else {
+            code
+        };
+        let segment = i32::from((code >> 4) & 0x7);
+        let step = i32::from(code & 0xF);
+        // The middle of the step
+        let magnitude = match self {
+            Companding::MuLaw => (((step << 1) + MU_LAW_BIAS) << segment) - MU_LAW_BIAS,
+            Companding::ALaw if segment == 0 => (step << 1) + 1,
+            Companding::ALaw => ((step << 1) + 33) << (segment - 1),
+        };
+        if code & 0x80 == 0 {
+            magnitude
+        } else {
+            -magnitude
+        }
+    }
+
+    /// Samples for each of the 256 codes, scaled so that the law's full scale maps to
+    /// `max_value + 1`.
+    pub(crate) fn expansion_table(self, max_value: u64, wire_format: bool) -> Vec<i128> {
+        let full_scale = 1_i128 << (self.sample_bits() - 1);
+        (0..=u8::MAX)
+            .map(|code| {
+                let sample = i128::from(self.decode(code, wire_format));
+                // Round half away from zero, so positive and negative samples stay symmetric
+                let scaled = sample * (i128::from(max_value) + 1);
+                let rounded = (scaled.abs() + full_scale / 2) / full_scale;
+                rounded * scaled.signum()
+            })
+            .collect()
+    }
+
+    /// Codes for each two's complement sample of `log2(size)` bits, shifted to the law's
+    /// sample size. `size` must be a power of two.
+    pub(crate) fn compression_table(self, size: usize, wire_format: bool) -> Vec<i128> {
+        let bits = size.trailing_zeros();
+        let sample_bits = self.sample_bits();
+        (0..size)
+            .map(|index| {
+                #[allow(clippy::cast_possible_wrap)]
+                let sample = if index >= size / 2 {
+                    index as i64 - size as i64
+                } else {
+                    index as i64
+                };
+                let sample = if bits > sample_bits {
+                    sample >> (bits - sample_bits)
+                } else {
+                    sample << (sample_bits - bits)
+                };
+                // Within the law's sample range after the shift
+                #[allow(clippy::cast_possible_truncation)]
+                let code = self.encode(sample as i32, wire_format);
+                i128::from(code)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mu_law_code_points() {
+        let law = Companding::MuLaw;
+        // Wire codes: 0xFF is zero, 0x80 and 0x00 the extremes
+        assert_eq!(law.encode(0, true), 0xFF);
+        assert_eq!(law.decode(0xFF, true), 0);
+        assert_eq!(law.decode(0x7F, true), 0);
+        assert_eq!(law.decode(0x80, true), 8031);
+        assert_eq!(law.decode(0x00, true), -8031);
+        assert_eq!(law.encode(8191, true), 0x80);
+        assert_eq!(law.encode(-8192, true), 0x00);
+        // The first segment steps by 2, the last by 256
+        assert_eq!(law.decode(0xFE, true), 2);
+        assert_eq!(law.decode(0x81, true), 8031 - 256);
+        // 100 + 33 is in segment 2, whose steps start at 128 and are 8 wide
+        assert_eq!(law.encode(100, false), 0x20);
+        assert_eq!(law.encode(-100, false), 0xA0);
+        assert_eq!(law.decode(0x20, false), 99);
+    }
+
+    #[test]
+    fn test_a_law_code_points() {
+        let law = Companding::ALaw;
+        // Wire codes alternate bits: 0xD5 and 0x55 are the smallest steps
+        assert_eq!(law.encode(0, true), 0xD5);
+        assert_eq!(law.encode(-1, true), 0x55);
+        assert_eq!(law.decode(0xD5, true), 1);
+        assert_eq!(law.decode(0x55, true), -1);
+        assert_eq!(law.decode(0xAA, true), 4032);
+        assert_eq!(law.decode(0x2A, true), -4032);
+        assert_eq!(law.encode(4095, true), 0xAA);
+        assert_eq!(law.encode(-4096, true), 0x2A);
+        // Segments 0 and 1 share a step size of 2
+        assert_eq!(law.decode(0x10, false), 33);
+        assert_eq!(law.decode(0x0F, false), 31);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for law in [Companding::MuLaw, Companding::ALaw] {
+            for wire_format in [false, true] {
+                for code in 0..=255 {
+                    let sample = law.decode(code, wire_format);
+                    // Only μ-law has a second code for zero, a negative one
+                    if law == Companding::MuLaw && sample == 0 {
+                        continue;
+                    }
+                    assert_eq!(law.encode(sample, wire_format), code, "{law:?}");
+                }
+            }
+            // Decoding is off by less than the largest step, including the clipped extremes
+            let largest_step = match law {
+                Companding::MuLaw => 256,
+                Companding::ALaw => 128,
+            };
+            let half = 1 << (law.sample_bits() - 1);
+            for sample in -half..half {
+                let decoded = law.decode(law.encode(sample, false), false);
+                assert!((decoded - sample).abs() < largest_step, "{law:?} {sample}");
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! 1D lookup tables in the `.cube` format read and written by Adobe and Resolve grading tools.
+//!
+//! A 1D `.cube` file holds `LUT_1D_SIZE` rows of red, green and blue outputs for inputs spaced
+//! evenly over the LUT's domain, `0.0..=1.0` unless `DOMAIN_MIN`/`DOMAIN_MAX` or
+//! `LUT_1D_INPUT_RANGE` say otherwise. The table index spans inputs `0.0..=1.0`, which are
+//! looked up in the domain, so a domain reaching outside them is rejected. Outputs are used as
+//! they are, so the usual `0.0..=1.0` range maps to `0..=max_value`.
+
+use super::icc::Channel;
+use super::{interpolate_samples, lowest_input_reaching, read_manifest_file, rises_monotonically};
+use proc_macro2::Span;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use syn::{Error, LitStr};
+
+/// Largest `LUT_1D_SIZE` allowed by the format.
+const MAX_LUT_SIZE: usize = 65_536;
+
+/// The columns of a `.cube` file given with the `cube_lut` parameter.
+#[derive(Debug, Clone)]
+pub(crate) struct CubeLut {
+    /// The path literal, where errors about the file are reported.
+    pub(crate) span: Span,
+    source: PathBuf,
+    /// Red, green and blue outputs.
+    columns: [Vec<f64>; 3],
+    /// The lowest and highest input of each column.
+    domains: [Domain; 3],
+}
+
+impl CubeLut {
+    /// Reads the `.cube` file at `path`, relative to `CARGO_MANIFEST_DIR`.
+    pub(crate) fn load(path: &LitStr) -> syn::Result<CubeLut> {
+        let (source, bytes) = read_manifest_file(path)?;
+        let (columns, domains) = String::from_utf8(bytes)
+            .map_err(|_| "the file is not UTF-8 text".to_string())
+            .and_then(|text| parse_cube(&text))
+            .map_err(|message| {
+                Error::new(
+                    path.span(),
+                    format!(
+                        "{} is not a valid 1D .cube LUT: {message}",
+                        source.display()
+                    ),
+                )
+            })?;
+        Ok(CubeLut {
+            span: path.span(),
+            source,
+            columns,
+            domains,
+        })
+    }
+
+    /// The column of `channel`, or the shared column when `channel` is not given and all three
+    /// are the same. Returns a message if no single column is selected.
+    pub(crate) fn curve(self, channel: Option<Channel>) -> Result<CubeCurve, String> {
+        let [red, green, blue] = self.columns;
+        let [red_domain, green_domain, blue_domain] = self.domains;
+        let (samples, domain) = match channel {
+            Some(Channel::Red) => (red, red_domain),
+            Some(Channel::Green) => (green, green_domain),
+            Some(Channel::Blue) => (blue, blue_domain),
+            Some(Channel::Gray) => {
+                return Err("Cube LUTs have red, green and blue columns, not gray".to_string())
+            }
+            None if red == green
+                && red == blue
+                && red_domain == green_domain
+                && red_domain == blue_domain =>
+            {
+                (red, red_domain)
+            }
+            None => {
+                return Err(
+                    "The LUT has a different curve for each color channel; select one with channel: red, green or blue"
+                        .to_string(),
+                )
+            }
+        };
+        let (low, high) = domain;
+        if low < 0.0 || high > 1.0 {
+            return Err(format!(
+                "The LUT's domain runs from {low} to {high}, but the table only covers inputs 0 to 1, so part of the LUT could never be reached"
+            ));
+        }
+        Ok(CubeCurve {
+            samples,
+            domain,
+            source: self.source,
+        })
+    }
+}
+
+/// One column of a `.cube` LUT.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CubeCurve {
+    samples: Vec<f64>,
+    domain: Domain,
+    source: PathBuf,
+}
+
+impl CubeCurve {
+    /// The file the LUT was read from.
+    pub(crate) fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Checks that the LUT rises monotonically, so that it can be decoded.
+    pub(crate) fn validate_decoding(&self) -> Result<(), String> {
+        if !rises_monotonically(|x| self.apply(x)) {
+            return Err(
+                "The cube LUT cannot be decoded because it does not rise monotonically".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// LUT input to output, interpolated linearly between the rows and holding the end rows
+    /// outside the domain.
+    pub(crate) fn apply(&self, x: f64) -> f64 {
+        let (low, high) = self.domain;
+        interpolate_samples(&self.samples, (x - low) / (high - low))
+    }
+
+    /// LUT output to the lowest input that produces it. Assumes the LUT rises, as checked by
+    /// [`validate_decoding`](Self::validate_decoding).
+    pub(crate) fn unapply(&self, y: f64) -> f64 {
+        lowest_input_reaching(|x| self.apply(x), y)
+    }
+}
+
+/// The inputs of the first and last row of a LUT.
+type Domain = (f64, f64);
+
+/// Reads the red, green and blue columns of a 1D `.cube` file, with the domain of each.
+fn parse_cube(text: &str) -> Result<([Vec<f64>; 3], [Domain; 3]), String> {
+    let mut size = None;
+    let mut columns: [Vec<f64>; 3] = Default::default();
+    let mut domains = [(0.0, 1.0); 3];
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: &str| format!("line {}: {message}", number + 1);
+        let mut fields = line.split_whitespace();
+        let keyword = fields.next().unwrap_or_default();
+        let numbers = || {
+            line.split_whitespace()
+                .skip(1)
+                .map(str::parse::<f64>)
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| error(&format!("{keyword} expects numbers")))
+        };
+        match keyword {
+            "TITLE" => {}
+            "LUT_3D_SIZE" => return Err(error("3D LUTs are not supported")),
+            "LUT_1D_SIZE" => {
+                let value = fields
+                    .next()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .filter(|value| (2..=MAX_LUT_SIZE).contains(value))
+                    .ok_or_else(|| {
+                        error(&format!("LUT_1D_SIZE must be from 2 to {MAX_LUT_SIZE}"))
+                    })?;
+                size = Some(value);
+            }
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let values = numbers()?;
+                if values.len() != 3 || values.iter().any(|value| !value.is_finite()) {
+                    return Err(error(&format!("{keyword} expects 3 numbers")));
+                }
+                for (domain, value) in domains.iter_mut().zip(values) {
+                    if keyword == "DOMAIN_MIN" {
+                        domain.0 = value;
+                    } else {
+                        domain.1 = value;
+                    }
+                }
+            }
+            "LUT_1D_INPUT_RANGE" => match numbers()?[..] {
+                [low, high] if low.is_finite() && high.is_finite() => domains = [(low, high); 3],
+                _ => return Err(error("LUT_1D_INPUT_RANGE expects 2 numbers")),
+            },
+            _ => {
+                let row = line
+                    .split_whitespace()
+                    .map(str::parse::<f64>)
+                    .collect::<Result<Vec<f64>, _>>()
+                    .ok()
+                    .filter(|row| row.len() == 3 && row.iter().all(|value| value.is_finite()))
+                    .ok_or_else(|| {
+                        error(&format!(
+                            "expected a keyword or three numbers, found `{line}`"
+                        ))
+                    })?;
+                if size.is_none() {
+                    return Err(error("data before LUT_1D_SIZE"));
+                }
+                for (column, value) in columns.iter_mut().zip(row) {
+                    column.push(value);
+                }
+            }
+        }
+    }
+
+    let size = size.ok_or_else(|| "there is no LUT_1D_SIZE".to_string())?;
+    if columns[0].len() != size {
+        return Err(format!(
+            "LUT_1D_SIZE is {size}, but there are {} rows",
+            columns[0].len()
+        ));
+    }
+    if let Some((low, high)) = domains.iter().find(|(low, high)| low >= high) {
+        return Err(format!(
+            "the domain must rise, but its minimum {low} is not below its maximum {high}"
+        ));
+    }
+    Ok((columns, domains))
+}
+
+/// Formats table `values` as a 1D `.cube` file with the same curve in each column, scaled so
+/// that `max_value` is `1.0`.
+pub(crate) fn format_cube(title: &str, values: &[i128], max_value: u64) -> String {
+    let mut text = format!("TITLE \"{title}\"\nLUT_1D_SIZE {}\n", values.len());
+    for &value in values {
+        #[allow(clippy::cast_precision_loss)]
+        let value = value as f64 / max_value as f64;
+        // Infallible for a String
+        let _ = writeln!(text, "{value:.6} {value:.6} {value:.6}");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (columns, domains) = parse_cube(
+            "# Written by a grading tool\nTITLE \"Warm\"\nLUT_1D_SIZE 3\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 1 1 1\n\n0 0 0\n0.6 0.5 0.4\n1 1 1\n",
+        )
+        .unwrap();
+        assert_eq!(columns[0], [0.0, 0.6, 1.0]);
+        assert_eq!(columns[2], [0.0, 0.4, 1.0]);
+        assert_eq!(domains, [(0.0, 1.0); 3]);
+
+        let lut = |columns: [Vec<f64>; 3]| CubeLut {
+            span: Span::call_site(),
+            source: PathBuf::new(),
+            columns,
+            domains,
+        };
+        let green = lut(columns.clone()).curve(Some(Channel::Green)).unwrap();
+        assert!((green.apply(0.25) - 0.25).abs() < 1e-12);
+        assert!((green.unapply(0.25) - 0.25).abs() < 1e-12);
+        assert!(lut(columns.clone()).curve(None).is_err());
+        assert!(lut(columns).curve(Some(Channel::Gray)).is_err());
+    }
+
+    #[test]
+    fn test_domain() {
+        let curve = |text: &str, channel| {
+            let (columns, domains) = parse_cube(text).unwrap();
+            let lut = CubeLut {
+                span: Span::call_site(),
+                source: PathBuf::new(),
+                columns,
+                domains,
+            };
+            lut.curve(channel)
+        };
+
+        // A LUT over 0.2..0.6: its rows span that part of the table
+        let narrow = curve(
+            "LUT_1D_SIZE 5\nLUT_1D_INPUT_RANGE 0.2 0.6\n0 0 0\n0.5 0.5 0.5\n0.7 0.7 0.7\n0.9 0.9 0.9\n1 1 1\n",
+            None,
+        )
+        .unwrap();
+        assert!((narrow.apply(0.3) - 0.5).abs() < 1e-12);
+        assert!((narrow.apply(0.4) - 0.7).abs() < 1e-12);
+        assert!((narrow.unapply(0.6) - 0.35).abs() < 1e-9);
+
+        // Per-channel domains, with inputs outside one held at the end rows
+        let text = "LUT_1D_SIZE 2\nDOMAIN_MIN 0 0.5 0\nDOMAIN_MAX 1 1 0.5\n0 0 0\n1 1 1\n";
+        let red = curve(text, Some(Channel::Red)).unwrap();
+        let green = curve(text, Some(Channel::Green)).unwrap();
+        let blue = curve(text, Some(Channel::Blue)).unwrap();
+        assert!((red.apply(0.25) - 0.25).abs() < 1e-12);
+        assert!(green.apply(0.25).abs() < 1e-12);
+        assert!((green.apply(0.75) - 0.5).abs() < 1e-12);
+        assert!((blue.apply(0.75) - 1.0).abs() < 1e-12);
+
+        // Rows outside the table's inputs could never be reached
+        let hdr = "LUT_1D_SIZE 2\nLUT_1D_INPUT_RANGE 0 4\n0 0 0\n1 1 1\n";
+        assert_eq!(
+            curve(hdr, None).unwrap_err(),
+            "The LUT's domain runs from 0 to 4, but the table only covers inputs 0 to 1, so part of the LUT could never be reached"
+        );
+        assert!(curve(
+            "LUT_1D_SIZE 2\nDOMAIN_MIN 0 0 -1\n0 0 0\n1 1 1\n",
+            Some(Channel::Blue)
+        )
+        .is_err());
+
+        let error =
+            parse_cube("LUT_1D_SIZE 2\nLUT_1D_INPUT_RANGE 1 0\n0 0 0\n1 1 1\n").unwrap_err();
+        assert_eq!(
+            error,
+            "the domain must rise, but its minimum 1 is not below its maximum 0"
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(parse_cube("0 0 0\n1 1 1\n").is_err());
+        assert!(parse_cube("LUT_3D_SIZE 2\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 1\n0 0 0\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 2\n0 0\n1 1\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 2\nDOMAIN_MIN 0\n0 0 0\n1 1 1\n").is_err());
+        let error = parse_cube("LUT_1D_SIZE 3\n0 0 0\n1 1 1\n").unwrap_err();
+        assert_eq!(error, "LUT_1D_SIZE is 3, but there are 2 rows");
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let text = format_cube("LED", &[0, 64, 255], 255);
+        assert!(text.starts_with("TITLE \"LED\"\nLUT_1D_SIZE 3\n0.000000 0.000000 0.000000\n"));
+        let (columns, _) = parse_cube(&text).unwrap();
+        assert_eq!(columns[1], [0.0, 0.250_980, 1.0]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Stage lighting dimmer curves, as offered by DMX lighting consoles.
+//!
+//! Each curve maps a fader level in `0.0..=1.0` to output level in `0.0..=1.0`, and all of them
+//! keep both ends fixed, so `0` stays off and a full fader gives full output.
+
+/// A dimmer curve selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Dimmer {
+    /// Output follows the fader, `x`.
+    Linear,
+    /// Square law, `x²`, which spreads out the low end of the fader.
+    SquareLaw,
+    /// Inverse square law, `√x`, which spreads out the top end of the fader.
+    InverseSquareLaw,
+    /// The cubic smoothstep `3x² - 2x³`, slow at both ends and steepest at half.
+    SCurve,
+}
+
+impl Dimmer {
+    /// Names accepted by the `curve` parameter, in the same order as [`Dimmer::from_name`].
+    pub(crate) const NAMES: [&'static str; 4] =
+        ["linear", "square_law", "inverse_square_law", "s_curve"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Dimmer> {
+        match name {
+            "linear" => Some(Dimmer::Linear),
+            "square_law" => Some(Dimmer::SquareLaw),
+            "inverse_square_law" => Some(Dimmer::InverseSquareLaw),
+            "s_curve" => Some(Dimmer::SCurve),
+            _ => None,
+        }
+    }
+
+    /// Fader level to output level.
+    pub(crate) fn to_output(self, level: f64) -> f64 {
+        match self {
+            Dimmer::Linear => level,
+            Dimmer::SquareLaw => level * level,
+            Dimmer::InverseSquareLaw => level.sqrt(),
+            Dimmer::SCurve => level * level * (3.0 - 2.0 * level),
+        }
+    }
+
+    /// Output level to the fader level that produces it.
+    pub(crate) fn to_level(self, output: f64) -> f64 {
+        match self {
+            Dimmer::Linear => output,
+            Dimmer::SquareLaw => output.sqrt(),
+            Dimmer::InverseSquareLaw => output * output,
+            // Trigonometric solution of the cubic on 0..=1
+            Dimmer::SCurve => 0.5 - ((1.0 - 2.0 * output).asin() / 3.0).sin(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Dimmer; 4] = [
+        Dimmer::Linear,
+        Dimmer::SquareLaw,
+        Dimmer::InverseSquareLaw,
+        Dimmer::SCurve,
+    ];
+
+    #[test]
+    fn test_end_points() {
+        for dimmer in ALL {
+            assert!(dimmer.to_output(0.0).abs() < 1e-12, "{dimmer:?}");
+            assert!((dimmer.to_output(1.0) - 1.0).abs() < 1e-12, "{dimmer:?}");
+            assert!(dimmer.to_level(0.0).abs() < 1e-12, "{dimmer:?}");
+            assert!((dimmer.to_level(1.0) - 1.0).abs() < 1e-12, "{dimmer:?}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for dimmer in ALL {
+            for i in 0..=50 {
+                let x = f64::from(i) / 50.0;
+                let round_trip = dimmer.to_level(dimmer.to_output(x));
+                assert!((round_trip - x).abs() < 1e-12, "{dimmer:?} at {x}");
+            }
+        }
+        // Half fader on the S-curve is half output, a quarter is 15.6%
+        assert!((Dimmer::SCurve.to_output(0.5) - 0.5).abs() < 1e-12);
+        assert!((Dimmer::SCurve.to_output(0.25) - 0.156_25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,233 @@
+//! Easing functions for animation, as popularized by Robert Penner.
+//!
+//! Each function is defined by its ease-in form over `0.0..=1.0`; the ease-out and ease-in-out
+//! forms are derived from it by mirroring, so every easing starts at `0.0` and ends at `1.0`.
+//! `back` and `elastic` overshoot that range on the way. As in Penner's equations, their
+//! ease-in-out forms mirror an ease-in with different constants.
+
+use std::f64::consts::PI;
+
+/// Overshoot of the `back` easing, about 10%.
+const BACK_OVERSHOOT: f64 = 1.701_58;
+/// Scale of [`BACK_OVERSHOOT`] in the ease-in-out form, which keeps the overshoot about 10%
+/// when each half is squeezed into half the time.
+const BACK_IN_OUT_SCALE: f64 = 1.525;
+/// Angular frequency of the `elastic` easing, three oscillations per unit of time.
+const ELASTIC_FREQUENCY: f64 = 20.0 * PI / 3.0;
+/// Angular frequency of each half of the ease-in-out `elastic` easing.
+const ELASTIC_IN_OUT_FREQUENCY: f64 = 2.0 * PI / 4.5;
+
+/// The shape of an easing function, named by its ease-in form.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EasingFunction {
+    /// `x²`
+    Quad,
+    /// `x³`
+    Cubic,
+    /// `1 - cos(x π / 2)`
+    Sine,
+    /// `2 ^ (10x - 10)`, and `0` at `x = 0`
+    Expo,
+    /// `(s + 1) x³ - s x²`, pulling back below zero before accelerating
+    Back,
+    /// `-2 ^ (10x - 10) sin((10x - 10.75) 2π / 3)`, a decaying oscillation
+    Elastic,
+    /// The mirror image of a ball bouncing to rest
+    Bounce,
+}
+
+/// Which end of the animation an easing slows down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EasingMode {
+    /// Slow start.
+    In,
+    /// Slow end, `1 - f(1 - x)`.
+    Out,
+    /// Slow start and end: the ease-in over the first half and the ease-out over the second,
+    /// with the constants of [`EasingFunction::ease_in_half`].
+    InOut,
+}
+
+/// An easing curve selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Easing {
+    pub(crate) function: EasingFunction,
+    pub(crate) mode: EasingMode,
+}
+
+impl Easing {
+    /// Names accepted by the `curve` parameter: `ease_in_`, `ease_out_` or `ease_in_out_`
+    /// followed by one of the functions.
+    pub(crate) const NAMES: [&'static str; 21] = [
+        "ease_in_quad",
+        "ease_out_quad",
+        "ease_in_out_quad",
+        "ease_in_cubic",
+        "ease_out_cubic",
+        "ease_in_out_cubic",
+        "ease_in_sine",
+        "ease_out_sine",
+        "ease_in_out_sine",
+        "ease_in_expo",
+        "ease_out_expo",
+        "ease_in_out_expo",
+        "ease_in_back",
+        "ease_out_back",
+        "ease_in_out_back",
+        "ease_in_elastic",
+        "ease_out_elastic",
+        "ease_in_out_elastic",
+        "ease_in_bounce",
+        "ease_out_bounce",
+        "ease_in_out_bounce",
+    ];
+
+    pub(crate) fn from_name(name: &str) -> Option<Easing> {
+        let (mode, function) = if let Some(function) = name.strip_prefix("ease_in_out_") {
+            (EasingMode::InOut, function)
+        } else if let Some(function) = name.strip_prefix("ease_in_") {
+            (EasingMode::In, function)
+        } else if let Some(function) = name.strip_prefix("ease_out_") {
+            (EasingMode::Out, function)
+        } else {
+            return None;
+        };
+        let function = match function {
+            "quad" => EasingFunction::Quad,
+            "cubic" => EasingFunction::Cubic,
+            "sine" => EasingFunction::Sine,
+            "expo" => EasingFunction::Expo,
+            "back" => EasingFunction::Back,
+            "elastic" => EasingFunction::Elastic,
+            "bounce" => EasingFunction::Bounce,
+            _ => return None,
+        };
+        Some(Easing { function, mode })
+    }
+
+    /// Animation progress in `0.0..=1.0` to the eased value.
+    pub(crate) fn apply(self, t: f64) -> f64 {
+        let ease_in = |t| self.function.ease_in(t);
+        match self.mode {
+            EasingMode::In => ease_in(t),
+            EasingMode::Out => 1.0 - ease_in(1.0 - t),
+            EasingMode::InOut => {
+                let ease_in_half = |t| self.function.ease_in_half(t);
+                if t < 0.5 {
+                    ease_in_half(2.0 * t) / 2.0
+                } else {
+                    1.0 - ease_in_half(2.0 - 2.0 * t) / 2.0
+                }
+            }
+        }
+    }
+}
+
+impl EasingFunction {
+    fn ease_in(self, t: f64) -> f64 {
+        match self {
+            EasingFunction::Quad => t * t,
+            EasingFunction::Cubic => t * t * t,
+            EasingFunction::Sine => 1.0 - (t * PI / 2.0).cos(),
+            EasingFunction::Expo => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2_f64.powf(10.0 * t - 10.0)
+                }
+            }
+            EasingFunction::Back => t * t * ((BACK_OVERSHOOT + 1.0) * t - BACK_OVERSHOOT),
+            EasingFunction::Elastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    t.clamp(0.0, 1.0)
+                } else {
+                    -(2_f64.powf(10.0 * t - 10.0)) * ((t - 1.075) * ELASTIC_FREQUENCY).sin()
+                }
+            }
+            EasingFunction::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+
+    /// The ease-in that each half of the ease-in-out form mirrors. Penner's `back` overshoots
+    /// by `s * 1.525` there, and `elastic` oscillates at `2π / 4.5` with a phase of `11.125`.
+    fn ease_in_half(self, t: f64) -> f64 {
+        match self {
+            EasingFunction::Back => {
+                let overshoot = BACK_OVERSHOOT * BACK_IN_OUT_SCALE;
+                t * t * ((overshoot + 1.0) * t - overshoot)
+            }
+            EasingFunction::Elastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    t.clamp(0.0, 1.0)
+                } else {
+                    -(2_f64.powf(10.0 * t - 10.0))
+                        * ((10.0 * t - 11.125) * ELASTIC_IN_OUT_FREQUENCY).sin()
+                }
+            }
+            _ => self.ease_in(t),
+        }
+    }
+}
+
+/// A ball dropped at `0.0` that lands at `1.0` and bounces three times, losing height each time.
+fn bounce_out(t: f64) -> f64 {
+    const GRAVITY: f64 = 7.5625;
+    const STEP: f64 = 2.75;
+    if t < 1.0 / STEP {
+        GRAVITY * t * t
+    } else if t < 2.0 / STEP {
+        let t = t - 1.5 / STEP;
+        GRAVITY * t * t + 0.75
+    } else if t < 2.5 / STEP {
+        let t = t - 2.25 / STEP;
+        GRAVITY * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / STEP;
+        GRAVITY * t * t + 0.984_375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for name in Easing::NAMES {
+            assert!(Easing::from_name(name).is_some(), "{name}");
+        }
+        assert!(Easing::from_name("ease_sideways_quad").is_none());
+        assert!(Easing::from_name("ease_in_quartic").is_none());
+    }
+
+    #[test]
+    fn test_end_points() {
+        for name in Easing::NAMES {
+            let easing = Easing::from_name(name).unwrap();
+            assert!(easing.apply(0.0).abs() < 1e-12, "{name}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-12, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        let easing = |name| Easing::from_name(name).unwrap();
+        assert!((easing("ease_in_cubic").apply(0.5) - 0.125).abs() < 1e-12);
+        assert!((easing("ease_out_quad").apply(0.5) - 0.75).abs() < 1e-12);
+        assert!((easing("ease_in_out_sine").apply(0.5) - 0.5).abs() < 1e-12);
+        assert!((easing("ease_out_sine").apply(0.5) - 0.5_f64.sqrt()).abs() < 1e-12);
+        // Back dips about 10% below the start, elastic overshoots the end
+        assert!((easing("ease_in_back").apply(0.4) + 0.099_352).abs() < 1e-6);
+        assert!(easing("ease_out_elastic").apply(0.1) > 1.0);
+        // Penner's ease-in-out back and elastic, as in CSS easing references
+        let in_out_back = easing("ease_in_out_back");
+        assert!((in_out_back.apply(0.2) + 0.092_556).abs() < 1e-6);
+        assert!((in_out_back.apply(0.9) - 1.037_519).abs() < 1e-6);
+        let in_out_elastic = easing("ease_in_out_elastic");
+        assert!((in_out_elastic.apply(0.3) - 0.023_939).abs() < 1e-6);
+        assert!((in_out_elastic.apply(0.8) - 1.003_906).abs() < 1e-6);
+        // The ball lands at 1 / 2.75 and bounces
+        assert!((easing("ease_out_bounce").apply(1.0 / 2.75) - 1.0).abs() < 1e-12);
+        assert!(easing("ease_out_bounce").apply(0.5) < 1.0);
+    }
+}
@@ -0,0 +1,507 @@
+//! Curves written as math expressions in the normalized input `x`.
+//!
+//! The expression uses Rust syntax and is parsed by `syn`, then checked into a small tree of
+//! numbers and conditions that keeps the span of every operator and function name, so that both
+//! unsupported syntax and values that evaluate to NaN or infinity are reported inside the
+//! expression.
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::{BinOp, Error, UnOp};
+
+/// A source span that does not take part in comparisons, so that curves compare by value.
+#[derive(Debug, Clone, Copy)]
+struct Location(Span);
+
+impl PartialEq for Location {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// A checked curve expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Expression {
+    root: Number,
+    location: Location,
+}
+
+/// A numeric subexpression.
+#[derive(Debug, Clone, PartialEq)]
+enum Number {
+    Literal(f64),
+    Input,
+    Negate(Box<Number>),
+    Arithmetic(Arithmetic, Box<Number>, Box<Number>, Location),
+    Call(Function, Vec<Number>, Location),
+    If(Box<Condition>, Box<Number>, Box<Number>),
+}
+
+/// A boolean subexpression, only allowed as the condition of an `if`.
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Compare(Comparison, Number, Number),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+/// Named constants, usable as bare identifiers.
+const CONSTANTS: [(&str, f64); 3] = [
+    ("PI", std::f64::consts::PI),
+    ("E", std::f64::consts::E),
+    ("TAU", std::f64::consts::TAU),
+];
+
+/// A function of one or more numbers, callable as `f(a, b)` or as the method `a.f(b)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Powf,
+    Sqrt,
+    Cbrt,
+    Exp,
+    Ln,
+    Log,
+    Log10,
+    Log2,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Tanh,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Function {
+    /// Names of the supported functions, for error messages.
+    const NAMES: [&'static str; 23] = [
+        "powf", "powi", "sqrt", "cbrt", "exp", "ln", "log", "log10", "log2", "sin", "cos", "tan",
+        "asin", "acos", "atan", "tanh", "abs", "floor", "ceil", "round", "min", "max", "clamp",
+    ];
+
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            // Integer powers of a float are still floats, so powi is only an alias
+            "powf" | "powi" => Function::Powf,
+            "sqrt" => Function::Sqrt,
+            "cbrt" => Function::Cbrt,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "log" => Function::Log,
+            "log10" => Function::Log10,
+            "log2" => Function::Log2,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "asin" => Function::Asin,
+            "acos" => Function::Acos,
+            "atan" => Function::Atan,
+            "tanh" => Function::Tanh,
+            "abs" => Function::Abs,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "round" => Function::Round,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "clamp" => Function::Clamp,
+            _ => return None,
+        })
+    }
+
+    /// Number of arguments, counting the receiver of a method call.
+    fn arity(self) -> usize {
+        match self {
+            Function::Powf | Function::Log | Function::Min | Function::Max => 2,
+            Function::Clamp => 3,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        let a = args[0];
+        match self {
+            Function::Powf => a.powf(args[1]),
+            Function::Sqrt => a.sqrt(),
+            Function::Cbrt => a.cbrt(),
+            Function::Exp => a.exp(),
+            Function::Ln => a.ln(),
+            Function::Log => a.log(args[1]),
+            Function::Log10 => a.log10(),
+            Function::Log2 => a.log2(),
+            Function::Sin => a.sin(),
+            Function::Cos => a.cos(),
+            Function::Tan => a.tan(),
+            Function::Asin => a.asin(),
+            Function::Acos => a.acos(),
+            Function::Atan => a.atan(),
+            Function::Tanh => a.tanh(),
+            Function::Abs => a.abs(),
+            Function::Floor => a.floor(),
+            Function::Ceil => a.ceil(),
+            Function::Round => a.round(),
+            Function::Min => a.min(args[1]),
+            Function::Max => a.max(args[1]),
+            Function::Clamp => a.max(args[1]).min(args[2]),
+        }
+    }
+}
+
+impl Expression {
+    /// Checks a parsed Rust expression, reporting unsupported syntax at its location.
+    pub(crate) fn new(expr: &syn::Expr) -> syn::Result<Expression> {
+        Ok(Expression {
+            root: number(expr)?,
+            location: Location(expr.span()),
+        })
+    }
+
+    /// Span of the whole expression.
+    pub(crate) fn span(&self) -> Span {
+        self.location.0
+    }
+
+    /// Evaluates the expression at `x`, returning NaN if any part of it is not finite.
+    pub(crate) fn eval(&self, x: f64) -> f64 {
+        self.try_eval(x).unwrap_or(f64::NAN)
+    }
+
+    /// Evaluates the expression at `x`, reporting the innermost operation that produced a
+    /// value that is not finite.
+    pub(crate) fn try_eval(&self, x: f64) -> syn::Result<f64> {
+        self.root.eval(x)
+    }
+}
+
+impl Number {
+    fn eval(&self, x: f64) -> syn::Result<f64> {
+        match self {
+            Number::Literal(value) => Ok(*value),
+            Number::Input => Ok(x),
+            Number::Negate(operand) => Ok(-operand.eval(x)?),
+            Number::Arithmetic(op, left, right, location) => {
+                let (left, right) = (left.eval(x)?, right.eval(x)?);
+                let value = match op {
+                    Arithmetic::Add => left + right,
+                    Arithmetic::Subtract => left - right,
+                    Arithmetic::Multiply => left * right,
+                    Arithmetic::Divide => left / right,
+                    Arithmetic::Remainder => left % right,
+                };
+                finite(value, x, *location)
+            }
+            Number::Call(function, args, location) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(x))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                finite(function.apply(&args), x, *location)
+            }
+            Number::If(condition, then_branch, else_branch) => {
+                if condition.eval(x)? {
+                    then_branch.eval(x)
+                } else {
+                    else_branch.eval(x)
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn eval(&self, x: f64) -> syn::Result<bool> {
+        Ok(match self {
+            Condition::Compare(op, left, right) => {
+                let (left, right) = (left.eval(x)?, right.eval(x)?);
+                match op {
+                    Comparison::Less => left < right,
+                    Comparison::LessOrEqual => left <= right,
+                    Comparison::Greater => left > right,
+                    Comparison::GreaterOrEqual => left >= right,
+                    #[allow(clippy::float_cmp)] // written by the user
+                    Comparison::Equal => left == right,
+                    #[allow(clippy::float_cmp)]
+                    Comparison::NotEqual => left != right,
+                }
+            }
+            Condition::And(left, right) => left.eval(x)? && right.eval(x)?,
+            Condition::Or(left, right) => left.eval(x)? || right.eval(x)?,
+            Condition::Not(operand) => !operand.eval(x)?,
+        })
+    }
+}
+
+fn finite(value: f64, x: f64, location: Location) -> syn::Result<f64> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(Error::new(
+            location.0,
+            format!("Expression evaluates to {value} at x = {x}"),
+        ))
+    }
+}
+
+/// Checks a numeric subexpression.
+fn number(expr: &syn::Expr) -> syn::Result<Number> {
+    match expr {
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Float(value) => Ok(Number::Literal(value.base10_parse()?)),
+            syn::Lit::Int(value) => Ok(Number::Literal(value.base10_parse()?)),
+            _ => Err(Error::new(lit.span(), "Expected a number")),
+        },
+        syn::Expr::Path(path) => {
+            let Some(ident) = path.path.get_ident() else {
+                return Err(Error::new(path.span(), "Expected x or a constant"));
+            };
+            if ident == "x" {
+                return Ok(Number::Input);
+            }
+            CONSTANTS
+                .iter()
+                .find(|(name, _)| ident == name)
+                .map(|(_, value)| Number::Literal(*value))
+                .ok_or_else(|| {
+                    Error::new(
+                        ident.span(),
+                        format!("Unknown variable: {ident}. Expressions can use x, PI, E and TAU"),
+                    )
+                })
+        }
+        syn::Expr::Paren(paren) => number(&paren.expr),
+        syn::Expr::Group(group) => number(&group.expr),
+        syn::Expr::Block(block) if block.label.is_none() => block_number(&block.block),
+        syn::Expr::Unary(unary) => match unary.op {
+            UnOp::Neg(_) => Ok(Number::Negate(Box::new(number(&unary.expr)?))),
+            _ => Err(Error::new(unary.span(), "Expected a number")),
+        },
+        syn::Expr::Binary(binary) => {
+            let op = match binary.op {
+                BinOp::Add(_) => Arithmetic::Add,
+                BinOp::Sub(_) => Arithmetic::Subtract,
+                BinOp::Mul(_) => Arithmetic::Multiply,
+                BinOp::Div(_) => Arithmetic::Divide,
+                BinOp::Rem(_) => Arithmetic::Remainder,
+                _ => return Err(Error::new(
+                    binary.span(),
+                    "Expected a number, found a condition (conditions can only be used in `if`)",
+                )),
+            };
+            Ok(Number::Arithmetic(
+                op,
+                Box::new(number(&binary.left)?),
+                Box::new(number(&binary.right)?),
+                Location(binary.op.span()),
+            ))
+        }
+        syn::Expr::Call(call) => {
+            let syn::Expr::Path(path) = &*call.func else {
+                return Err(Error::new(call.func.span(), "Expected a function name"));
+            };
+            let Some(ident) = path.path.get_ident() else {
+                return Err(Error::new(path.span(), "Expected a function name"));
+            };
+            let args = call.args.iter().map(number).collect::<syn::Result<_>>()?;
+            self::call(ident, args)
+        }
+        syn::Expr::MethodCall(call) => {
+            if call.turbofish.is_some() {
+                return Err(Error::new(call.span(), "Unexpected type arguments"));
+            }
+            let args = std::iter::once(&*call.receiver)
+                .chain(&call.args)
+                .map(number)
+                .collect::<syn::Result<_>>()?;
+            self::call(&call.method, args)
+        }
+        syn::Expr::If(expr_if) => {
+            let Some((_, else_branch)) = &expr_if.else_branch else {
+                return Err(Error::new(
+                    expr_if.span(),
+                    "An `if` expression needs an `else` branch",
+                ));
+            };
+            Ok(Number::If(
+                Box::new(condition(&expr_if.cond)?),
+                Box::new(block_number(&expr_if.then_branch)?),
+                Box::new(number(else_branch)?),
+            ))
+        }
+        _ => Err(Error::new(expr.span(), "Unsupported expression")),
+    }
+}
+
+/// Checks a block that holds a single numeric expression.
+fn block_number(block: &syn::Block) -> syn::Result<Number> {
+    match block.stmts.as_slice() {
+        [syn::Stmt::Expr(expr, None)] => number(expr),
+        _ => Err(Error::new(
+            block.span(),
+            "Expected a block with a single expression",
+        )),
+    }
+}
+
+/// Checks a call, using the function name as the location of its errors.
+fn call(ident: &syn::Ident, args: Vec<Number>) -> syn::Result<Number> {
+    let function = Function::from_name(&ident.to_string()).ok_or_else(|| {
+        Error::new(
+            ident.span(),
+            format!(
+                "Unknown function: {ident}. Supported functions are: {}",
+                Function::NAMES.join(", ")
+            ),
+        )
+    })?;
+    if args.len() != function.arity() {
+        return Err(Error::new(
+            ident.span(),
+            format!(
+                "{ident} takes {} arguments, counting the receiver of a method call, but {} were given",
+                function.arity(),
+                args.len()
+            ),
+        ));
+    }
+    Ok(Number::Call(function, args, Location(ident.span())))
+}
+
+/// Checks the condition of an `if` expression.
+fn condition(expr: &syn::Expr) -> syn::Result<Condition> {
+    match expr {
+        syn::Expr::Paren(paren) => condition(&paren.expr),
+        syn::Expr::Group(group) => condition(&group.expr),
+        syn::Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => {
+            Ok(Condition::Not(Box::new(condition(&unary.expr)?)))
+        }
+        syn::Expr::Binary(binary) => {
+            let compare = |op| {
+                Ok(Condition::Compare(
+                    op,
+                    number(&binary.left)?,
+                    number(&binary.right)?,
+                ))
+            };
+            match binary.op {
+                BinOp::Lt(_) => compare(Comparison::Less),
+                BinOp::Le(_) => compare(Comparison::LessOrEqual),
+                BinOp::Gt(_) => compare(Comparison::Greater),
+                BinOp::Ge(_) => compare(Comparison::GreaterOrEqual),
+                BinOp::Eq(_) => compare(Comparison::Equal),
+                BinOp::Ne(_) => compare(Comparison::NotEqual),
+                BinOp::And(_) => Ok(Condition::And(
+                    Box::new(condition(&binary.left)?),
+                    Box::new(condition(&binary.right)?),
+                )),
+                BinOp::Or(_) => Ok(Condition::Or(
+                    Box::new(condition(&binary.left)?),
+                    Box::new(condition(&binary.right)?),
+                )),
+                _ => Err(Error::new(
+                    binary.span(),
+                    "Expected a condition, found a number",
+                )),
+            }
+        }
+        _ => Err(Error::new(
+            expr.span(),
+            "Expected a condition such as `x < 0.5`",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> syn::Result<Expression> {
+        Expression::new(&syn::parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn test_eval() {
+        let expr = parse("x.powf(2.0) + 1").unwrap();
+        assert!((expr.eval(0.5) - 1.25).abs() < 1e-12);
+
+        // Function and method call forms are interchangeable
+        assert_eq!(
+            parse("powf(x, 2.2)").unwrap(),
+            parse("x.powf(2.2)").unwrap()
+        );
+        assert!((parse("max(x, 0.5)").unwrap().eval(0.2) - 0.5).abs() < 1e-12);
+        assert!((parse("-x.clamp(0.1, 0.9)").unwrap().eval(1.0) + 0.9).abs() < 1e-12);
+        assert!((parse("sin(PI * x)").unwrap().eval(0.5) - 1.0).abs() < 1e-12);
+        assert!((parse("{ (x * E).ln() }").unwrap().eval(1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_piecewise() {
+        // sRGB written out by hand
+        let expr = parse("if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }")
+            .unwrap();
+        assert!((expr.eval(0.02) - 0.02 / 12.92).abs() < 1e-12);
+        assert!((expr.eval(0.5) - 0.214_041).abs() < 1e-6);
+
+        let expr =
+            parse("if x < 0.25 || !(x < 0.75) { 0 } else if x == 0.5 { 2 } else { 1 }").unwrap();
+        assert!(expr.eval(0.1).abs() < 1e-12);
+        assert!((expr.eval(0.5) - 2.0).abs() < 1e-12);
+        assert!((expr.eval(0.6) - 1.0).abs() < 1e-12);
+        assert!(expr.eval(0.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        for (source, message) in [
+            ("x.gamma(2.2)", "Unknown function: gamma"),
+            ("y * 2.0", "Unknown variable: y"),
+            ("x.powf()", "powf takes 2 arguments"),
+            ("x < 0.5", "Expected a number, found a condition"),
+            ("if x { 1 } else { 0 }", "Expected a condition"),
+            ("if x < 0.5 { 1 }", "needs an `else` branch"),
+            ("\"x\"", "Expected a number"),
+            ("[x]", "Unsupported expression"),
+        ] {
+            let error = parse(source).unwrap_err().to_string();
+            assert!(error.contains(message), "{source}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let expr = parse("x.ln() * 2.0").unwrap();
+        assert!(expr.eval(0.0).is_nan());
+        let error = expr.try_eval(0.0).unwrap_err().to_string();
+        assert_eq!(error, "Expression evaluates to -inf at x = 0");
+        assert!(expr.try_eval(0.5).is_ok());
+
+        let expr = parse("1.0 / (x - 0.5)").unwrap();
+        assert!(expr.try_eval(0.5).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Least-squares power-law fits to measured samples.
+//!
+//! Inputs are divided by the last input and outputs by the last output, so the last sample
+//! is full scale and the fitted curve passes through `(1.0, 1.0)`. Residuals are measured in
+//! those normalized output units, and the table follows the fitted model, black offset
+//! included.
+
+use super::measured::Points;
+use std::path::PathBuf;
+
+/// Lowest and highest gamma considered by the fit.
+const GAMMA_RANGE: (f64, f64) = (0.05, 20.0);
+/// Gamma values sampled across [`GAMMA_RANGE`] to bracket the best fit.
+const SCAN_STEPS: u32 = 400;
+/// Relative distance from either end of [`GAMMA_RANGE`] within which a fit counts as pinned
+/// to it.
+const BOUND_TOLERANCE: f64 = 1e-6;
+/// Golden-section iterations that refine the bracket, enough for `f64` precision.
+const REFINE_ITERATIONS: u32 = 100;
+
+/// The model fitted to the samples with the `fit` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FitModel {
+    /// `output = input ^ gamma`
+    Gamma,
+    /// `output = black + (1 - black) * input ^ gamma`
+    GammaWithBlack,
+}
+
+impl FitModel {
+    /// Names accepted by the `fit` parameter.
+    pub(crate) const NAMES: [&'static str; 2] = ["gamma", "gamma_with_black"];
+
+    pub(crate) fn from_name(name: &str) -> Option<FitModel> {
+        match name {
+            "gamma" => Some(FitModel::Gamma),
+            "gamma_with_black" => Some(FitModel::GammaWithBlack),
+            _ => None,
+        }
+    }
+}
+
+/// A fitted power law and how well it matches the samples.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Fit {
+    pub(crate) gamma: f64,
+    /// Normalized output at zero input, for [`FitModel::GammaWithBlack`].
+    pub(crate) black_offset: Option<f64>,
+    /// Root mean square of the residuals.
+    pub(crate) rms_error: f64,
+    /// The CSV file the samples were read from, which the generated code depends on.
+    pub(crate) source: Option<PathBuf>,
+}
+
+impl Fit {
+    /// Fits `model` to `points`, returning a message describing the first problem.
+    pub(crate) fn new(points: Points, model: FitModel) -> Result<Fit, String> {
+        let Points { values, source, .. } = points;
+        let needed = match model {
+            FitModel::Gamma => 2,
+            FitModel::GammaWithBlack => 3,
+        };
+        if values.len() < needed {
+            return Err(format!(
+                "At least {needed} samples are needed to fit {} (got {})",
+                match model {
+                    FitModel::Gamma => "gamma",
+                    FitModel::GammaWithBlack => "gamma with a black offset",
+                },
+                values.len()
+            ));
+        }
+        if let Some((input, output)) = values
+            .iter()
+            .find(|(input, output)| !input.is_finite() || !output.is_finite())
+        {
+            return Err(format!("Sample ({input}, {output}) is not finite"));
+        }
+        if let Some((input, _)) = values.iter().find(|(input, _)| *input < 0.0) {
+            return Err(format!("Sample inputs must not be negative (got {input})"));
+        }
+        if let Some(pair) = values.windows(2).find(|pair| pair[1].0 <= pair[0].0) {
+            return Err(format!(
+                "Sample inputs must strictly increase, but {} is followed by {}",
+                pair[0].0, pair[1].0
+            ));
+        }
+        let (last_input, last_output) = values[values.len() - 1];
+        if last_output <= 0.0 {
+            return Err("The last sample must have a positive output".to_string());
+        }
+
+        let samples: Vec<(f64, f64)> = values
+            .iter()
+            .map(|(input, output)| (input / last_input, output / last_output))
+            .collect();
+        let gamma = minimize(|gamma| sum_of_squares(&samples, gamma, model).0);
+        // A fit pinned to either end of the range has not found a minimum
+        let (lowest, highest) = GAMMA_RANGE;
+        if gamma <= lowest * (1.0 + BOUND_TOLERANCE) || gamma >= highest * (1.0 - BOUND_TOLERANCE) {
+            return Err(format!(
+                "The best fit is at gamma {gamma:.4}, the edge of the range searched ({lowest} to {highest}), so the samples do not follow a power law"
+            ));
+        }
+        let (sum, black) = sum_of_squares(&samples, gamma, model);
+        if black >= 1.0 {
+            return Err(format!(
+                "The fitted black offset {black} is not below full scale, so the samples do not rise"
+            ));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rms_error = (sum / samples.len() as f64).sqrt();
+        Ok(Fit {
+            gamma,
+            black_offset: match model {
+                FitModel::Gamma => None,
+                FitModel::GammaWithBlack => Some(black),
+            },
+            rms_error,
+            source,
+        })
+    }
+
+    /// Normalized input to the normalized output of the fitted model.
+    pub(crate) fn to_output(&self, x: f64) -> f64 {
+        let black = self.black_offset.unwrap_or(0.0);
+        black + (1.0 - black) * x.powf(self.gamma)
+    }
+
+    /// Normalized output to the input that produces it, or `0.0` for outputs below black.
+    pub(crate) fn to_input(&self, y: f64) -> f64 {
+        let black = self.black_offset.unwrap_or(0.0);
+        ((y - black) / (1.0 - black))
+            .max(0.0)
+            .powf(1.0 / self.gamma)
+    }
+}
+
+/// Sum of squared residuals of `model` with `gamma`, and the black offset that minimizes it.
+///
+/// For a fixed gamma the model is linear in the black offset, so it has a closed form.
+fn sum_of_squares(samples: &[(f64, f64)], gamma: f64, model: FitModel) -> (f64, f64) {
+    let black = match model {
+        FitModel::Gamma => 0.0,
+        FitModel::GammaWithBlack => {
+            // output - p = black * (1 - p), with p = input ^ gamma
+            let (numerator, denominator) =
+                samples
+                    .iter()
+                    .fold((0.0, 0.0), |(numerator, denominator), &(input, output)| {
+                        let p = input.powf(gamma);
+                        (
+                            numerator + (1.0 - p) * (output - p),
+                            denominator + (1.0 - p) * (1.0 - p),
+                        )
+                    });
+            if denominator > 0.0 {
+                numerator / denominator
+            } else {
+                0.0
+            }
+        }
+    };
+    let sum = samples
+        .iter()
+        .map(|&(input, output)| {
+            let residual = black + (1.0 - black) * input.powf(gamma) - output;
+            residual * residual
+        })
+        .sum();
+    (sum, black)
+}
+
+/// Finds the gamma in [`GAMMA_RANGE`] that minimizes `error`, by scanning on a log scale and
+/// refining the best bracket with a golden-section search.
+fn minimize(error: impl Fn(f64) -> f64) -> f64 {
+    let (low, high) = (GAMMA_RANGE.0.ln(), GAMMA_RANGE.1.ln());
+    let step = (high - low) / f64::from(SCAN_STEPS);
+    let scan = |i: u32| low + f64::from(i) * step;
+    let best = (0..=SCAN_STEPS)
+        .min_by(|&a, &b| error(scan(a).exp()).total_cmp(&error(scan(b).exp())))
+        .unwrap_or(0);
+
+    let (mut a, mut b) = (
+        scan(best.saturating_sub(1)),
+        scan((best + 1).min(SCAN_STEPS)),
+    );
+    let ratio = (5_f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..REFINE_ITERATIONS {
+        let c = b - ratio * (b - a);
+        let d = a + ratio * (b - a);
+        if error(c.exp()) < error(d.exp()) {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+    f64::midpoint(a, b).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    fn points(values: Vec<(f64, f64)>) -> Points {
+        Points {
+            span: Span::call_site(),
+            values,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_exact_power_law() {
+        // Luminance in cd/m² of a display with gamma 2.4 and a 250 cd/m² white
+        let samples = (0..=8)
+            .map(|i| {
+                let input = f64::from(i) * 32.0;
+                (input, 250.0 * (input / 256.0).powf(2.4))
+            })
+            .collect();
+        let fit = Fit::new(points(samples), FitModel::Gamma).unwrap();
+        assert!((fit.gamma - 2.4).abs() < 1e-6);
+        assert!(fit.rms_error < 1e-9);
+        assert_eq!(fit.black_offset, None);
+    }
+
+    #[test]
+    fn test_black_offset() {
+        let samples: Vec<(f64, f64)> = (0..=10)
+            .map(|i| {
+                let input = f64::from(i) / 10.0;
+                (input, 0.02 + 0.98 * input.powf(2.2))
+            })
+            .collect();
+        let fit = Fit::new(points(samples.clone()), FitModel::GammaWithBlack).unwrap();
+        assert!((fit.gamma - 2.2).abs() < 1e-6);
+        assert!((fit.black_offset.unwrap() - 0.02).abs() < 1e-6);
+        assert!(fit.rms_error < 1e-9);
+        // The model is applied with its offset, and decodes outputs below black to zero
+        assert!((fit.to_output(0.0) - 0.02).abs() < 1e-6);
+        assert!((fit.to_output(0.5) - (0.02 + 0.98 * 0.5_f64.powf(2.2))).abs() < 1e-6);
+        assert!((fit.to_input(fit.to_output(0.3)) - 0.3).abs() < 1e-12);
+        assert!(fit.to_input(0.01).abs() < 1e-12);
+
+        // Without the offset the same data fits a lower gamma, less closely
+        let fit = Fit::new(points(samples), FitModel::Gamma).unwrap();
+        assert!(fit.gamma < 2.2);
+        assert!(fit.rms_error > 1e-3);
+    }
+
+    #[test]
+    fn test_invalid_samples() {
+        assert!(Fit::new(points(vec![(1.0, 1.0)]), FitModel::Gamma).is_err());
+        assert!(Fit::new(
+            points(vec![(0.0, 0.0), (1.0, 1.0)]),
+            FitModel::GammaWithBlack
+        )
+        .is_err());
+        assert!(Fit::new(points(vec![(-1.0, 0.0), (1.0, 1.0)]), FitModel::Gamma).is_err());
+        assert!(Fit::new(points(vec![(0.5, 0.2), (0.5, 1.0)]), FitModel::Gamma).is_err());
+        assert!(Fit::new(points(vec![(0.0, 0.0), (1.0, 0.0)]), FitModel::Gamma).is_err());
+    }
+
+    #[test]
+    fn test_fit_at_range_bound() {
+        // A step at the start wants a gamma near zero, one at the end an unbounded gamma
+        for samples in [
+            vec![(0.0, 0.0), (0.1, 1.0), (0.5, 1.0), (1.0, 1.0)],
+            vec![(0.0, 0.0), (0.5, 0.0), (0.99, 0.0), (1.0, 1.0)],
+        ] {
+            let error = Fit::new(points(samples), FitModel::Gamma).unwrap_err();
+            assert!(error.contains("the edge of the range searched"), "{error}");
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! DICOM PS3.14 Grayscale Standard Display Function.
+//!
+//! The GSDF maps just-noticeable-difference (JND) indices `1..=1023` to luminance in cd/m².
+//! A calibrated display spaces its digital driving levels evenly in JND index between its
+//! minimum and maximum luminance.
+
+/// Lowest luminance in cd/m² covered by the GSDF (JND index 1).
+pub(crate) const MIN_LUMINANCE: f64 = 0.05;
+/// Highest luminance in cd/m² covered by the GSDF (just below JND index 1023).
+pub(crate) const MAX_LUMINANCE: f64 = 3993.0;
+
+/// Coefficients of the rational polynomial in `ln(j)` giving `log10(L)` (PS3.14 equation 1).
+const A: f64 = -1.301_187_7;
+const B: f64 = -2.584_019_1e-2;
+const C: f64 = 8.024_263_6e-2;
+const D: f64 = -1.032_022_9e-1;
+const E: f64 = 1.364_669_9e-1;
+const F: f64 = 2.874_562_0e-2;
+const G: f64 = -2.546_840_4e-2;
+const H: f64 = -3.197_897_7e-3;
+const K: f64 = 1.299_263_4e-4;
+const M: f64 = 1.363_533_4e-3;
+
+/// Number of bisection steps used to invert [`luminance`] exactly.
+const BISECTION_STEPS: usize = 64;
+
+/// Luminance in cd/m² of JND index `j`.
+pub(crate) fn luminance(j: f64) -> f64 {
+    let x = j.ln();
+    let numerator = A + x * (C + x * (E + x * (G + x * M)));
+    let denominator = 1.0 + x * (B + x * (D + x * (F + x * (H + x * K))));
+    10_f64.powf(numerator / denominator)
+}
+
+/// JND index whose [`luminance`] is `target`, anywhere in the GSDF range.
+pub(crate) fn jnd_index(target: f64) -> f64 {
+    solve_jnd_index(target, 1.0, 1023.0)
+}
+
+/// JND index in `low..=high` whose [`luminance`] is `target`, found by bisection.
+pub(crate) fn solve_jnd_index(target: f64, mut low: f64, mut high: f64) -> f64 {
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (low + high);
+        if luminance(mid) < target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    0.5 * (low + high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gsdf_range() {
+        assert!((luminance(1.0) - MIN_LUMINANCE).abs() < 1e-4);
+        assert!(luminance(1023.0) > MAX_LUMINANCE);
+        assert!(luminance(1023.0) < MAX_LUMINANCE + 1.0);
+    }
+
+    /// Entries of PS3.14 Table B-1, `(j, L)`, with `L` given to four decimals.
+    const TABLE_B1: [(f64, f64); 5] = [
+        (1.0, 0.0500),
+        (2.0, 0.0547),
+        (3.0, 0.0594),
+        (4.0, 0.0643),
+        (1023.0, 3993.4040),
+    ];
+
+    #[test]
+    fn test_gsdf_table_values() {
+        for (j, expected) in TABLE_B1 {
+            let value = luminance(j);
+            assert!(
+                (value - expected).abs() < 5e-5 || (value / expected - 1.0).abs() < 1e-4,
+                "L({j}) = {value}, expected {expected}"
+            );
+            // The published luminance is solved back to its index to a small fraction of a JND
+            assert!((jnd_index(expected) - j).abs() < 0.01);
+        }
+    }
+}
@@ -0,0 +1,449 @@
+//! Tone response curves read from ICC profiles.
+//!
+//! Only the parts of ICC.1 needed for the `rTRC`, `gTRC`, `bTRC` and `kTRC` tags are parsed:
+//! the header signature, the tag table, and the `curv` and `para` tag types. Each curve maps a
+//! device value in `0.0..=1.0` to linear light in `0.0..=1.0`.
+
+use super::{interpolate_samples, lowest_input_reaching, read_manifest_file, rises_monotonically};
+use proc_macro2::Span;
+use std::path::{Path, PathBuf};
+use syn::{Error, LitStr};
+
+/// Length of the profile header, which the tag table follows.
+const HEADER_SIZE: usize = 128;
+/// Bytes of each tag table entry: signature, offset and size.
+const TAG_ENTRY_SIZE: usize = 12;
+
+/// The color channel whose curve fills the table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Channel {
+    Red,
+    Green,
+    Blue,
+    /// The single curve of a grayscale profile.
+    Gray,
+}
+
+impl Channel {
+    /// Names accepted by the `channel` parameter.
+    pub(crate) const NAMES: [&'static str; 4] = ["red", "green", "blue", "gray"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Channel> {
+        match name {
+            "red" => Some(Channel::Red),
+            "green" => Some(Channel::Green),
+            "blue" => Some(Channel::Blue),
+            "gray" => Some(Channel::Gray),
+            _ => None,
+        }
+    }
+
+    /// Suffix of the table generated for the channel when an RGB profile is split.
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            Channel::Red => "RED",
+            Channel::Green => "GREEN",
+            Channel::Blue => "BLUE",
+            Channel::Gray => "GRAY",
+        }
+    }
+
+    /// Signature of the tag holding the channel's curve.
+    fn tag(self) -> &'static [u8; 4] {
+        match self {
+            Channel::Red => b"rTRC",
+            Channel::Green => b"gTRC",
+            Channel::Blue => b"bTRC",
+            Channel::Gray => b"kTRC",
+        }
+    }
+}
+
+/// A tone response curve as stored in the profile.
+#[derive(Debug, Clone, PartialEq)]
+enum Trc {
+    /// A `curv` tag with a single entry, `x ^ gamma`.
+    Gamma(f64),
+    /// A `curv` tag with evenly spaced samples, interpolated linearly.
+    Sampled(Vec<f64>),
+    /// A `para` tag: the function type and its parameters `g, a, b, c, d, e, f`.
+    Parametric { function: u16, parameters: [f64; 7] },
+}
+
+/// The tone response curves of a profile given with the `icc_profile` parameter.
+#[derive(Debug, Clone)]
+pub(crate) struct IccProfile {
+    /// The path literal, where errors about the profile are reported.
+    pub(crate) span: Span,
+    source: PathBuf,
+    curves: Vec<(Channel, Trc)>,
+}
+
+impl IccProfile {
+    /// Reads the profile at `path`, relative to `CARGO_MANIFEST_DIR`.
+    pub(crate) fn load(path: &LitStr) -> syn::Result<IccProfile> {
+        let (source, bytes) = read_manifest_file(path)?;
+        let curves = parse_profile(&bytes).map_err(|message| {
+            Error::new(
+                path.span(),
+                format!("{} is not a valid ICC profile: {message}", source.display()),
+            )
+        })?;
+        Ok(IccProfile {
+            span: path.span(),
+            source,
+            curves,
+        })
+    }
+
+    /// The curves to use when `channel` is not given: the only curve of a grayscale profile, or
+    /// the red, green and blue curves of an RGB profile.
+    pub(crate) fn default_curves(&self) -> Result<Vec<(Channel, IccCurve)>, String> {
+        let channels = match self.curves[..] {
+            [(channel, _)] => vec![channel],
+            _ => vec![Channel::Red, Channel::Green, Channel::Blue],
+        };
+        channels
+            .into_iter()
+            .map(|channel| Ok((channel, self.curve(channel)?)))
+            .collect()
+    }
+
+    /// The curve of `channel`, returning a message if the profile does not have one.
+    pub(crate) fn curve(&self, channel: Channel) -> Result<IccCurve, String> {
+        let trc = self
+            .curves
+            .iter()
+            .find(|(candidate, _)| *candidate == channel)
+            .map(|(_, trc)| trc.clone())
+            .ok_or_else(|| {
+                format!(
+                    "The profile has no {} tag",
+                    String::from_utf8_lossy(channel.tag())
+                )
+            })?;
+        Ok(IccCurve {
+            trc,
+            source: self.source.clone(),
+        })
+    }
+}
+
+/// One channel's tone response curve, selected with the `channel` parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IccCurve {
+    trc: Trc,
+    source: PathBuf,
+}
+
+impl IccCurve {
+    /// The profile the curve was read from.
+    pub(crate) fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Checks that the curve rises monotonically, so that it can be decoded.
+    pub(crate) fn validate_decoding(&self) -> Result<(), String> {
+        if !rises_monotonically(|x| self.to_linear(x)) {
+            return Err(
+                "The ICC curve cannot be decoded because it does not rise monotonically"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Device value to linear light.
+    pub(crate) fn to_linear(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match &self.trc {
+            Trc::Gamma(gamma) => x.powf(*gamma),
+            Trc::Sampled(samples) => interpolate_samples(samples, x),
+            Trc::Parametric {
+                function,
+                parameters,
+            } => parametric(*function, parameters, x).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Linear light to the lowest device value that produces it. Assumes the curve rises, as
+    /// checked by [`validate_decoding`](Self::validate_decoding).
+    pub(crate) fn to_device(&self, y: f64) -> f64 {
+        lowest_input_reaching(|x| self.to_linear(x), y)
+    }
+}
+
+/// Evaluates a `para` curve, with the parameter names of ICC.1 table 68.
+#[allow(clippy::many_single_char_names)]
+fn parametric(function: u16, parameters: &[f64; 7], x: f64) -> f64 {
+    let [g, a, b, c, d, e, f] = *parameters;
+    let power = |x: f64| (a * x + b).max(0.0).powf(g);
+    match function {
+        0 => x.powf(g),
+        1 => {
+            if x >= -b / a {
+                power(x)
+            } else {
+                0.0
+            }
+        }
+        2 => {
+            if x >= -b / a {
+                power(x) + c
+            } else {
+                c
+            }
+        }
+        3 => {
+            if x >= d {
+                power(x)
+            } else {
+                c * x
+            }
+        }
+        _ => {
+            if x >= d {
+                power(x) + e
+            } else {
+                c * x + f
+            }
+        }
+    }
+}
+
+/// Reads the tone response curves from the bytes of a profile.
+fn parse_profile(bytes: &[u8]) -> Result<Vec<(Channel, Trc)>, String> {
+    if bytes.len() < HEADER_SIZE + 4 {
+        return Err(format!(
+            "the file is {} bytes, shorter than a profile header",
+            bytes.len()
+        ));
+    }
+    if &bytes[36..40] != b"acsp" {
+        return Err("the header does not have the 'acsp' signature".to_string());
+    }
+    let declared = read_u32(bytes, 0)? as usize;
+    if declared > bytes.len() {
+        return Err(format!(
+            "the header declares {declared} bytes, but the file has {}",
+            bytes.len()
+        ));
+    }
+
+    let tag_count = read_u32(bytes, HEADER_SIZE)? as usize;
+    let mut curves = Vec::new();
+    for index in 0..tag_count {
+        let entry = HEADER_SIZE + 4 + index * TAG_ENTRY_SIZE;
+        let signature = read_bytes(bytes, entry, 4)?;
+        let Some(channel) = [Channel::Red, Channel::Green, Channel::Blue, Channel::Gray]
+            .into_iter()
+            .find(|channel| channel.tag() == signature)
+        else {
+            continue;
+        };
+        let offset = read_u32(bytes, entry + 4)? as usize;
+        let size = read_u32(bytes, entry + 8)? as usize;
+        let data = read_bytes(bytes, offset, size).map_err(|_| {
+            format!(
+                "the {} tag at offset {offset} with {size} bytes runs past the end of the file",
+                String::from_utf8_lossy(signature)
+            )
+        })?;
+        let trc = parse_trc(data).map_err(|message| {
+            format!("the {} tag {message}", String::from_utf8_lossy(signature))
+        })?;
+        curves.push((channel, trc));
+    }
+
+    if curves.is_empty() {
+        return Err("it has no rTRC, gTRC, bTRC or kTRC tag".to_string());
+    }
+    Ok(curves)
+}
+
+/// Reads a `curv` or `para` tag.
+fn parse_trc(data: &[u8]) -> Result<Trc, String> {
+    let tag_type = read_bytes(data, 0, 4)?;
+    match tag_type {
+        b"curv" => {
+            let count = read_u32(data, 8)? as usize;
+            let entries = (0..count)
+                .map(|index| read_u16(data, 12 + 2 * index))
+                .collect::<Result<Vec<u16>, String>>()?;
+            Ok(match entries[..] {
+                [] => Trc::Gamma(1.0),
+                // u8Fixed8Number
+                [gamma] => Trc::Gamma(f64::from(gamma) / 256.0),
+                _ => Trc::Sampled(
+                    entries
+                        .iter()
+                        .map(|&entry| f64::from(entry) / f64::from(u16::MAX))
+                        .collect(),
+                ),
+            })
+        }
+        b"para" => {
+            let function = read_u16(data, 8)?;
+            let count = match function {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => return Err(format!("has unknown parametric function type {function}")),
+            };
+            let mut parameters = [0.0; 7];
+            for (index, parameter) in parameters.iter_mut().take(count).enumerate() {
+                // s15Fixed16Number
+                #[allow(clippy::cast_possible_wrap)]
+                let fixed = read_u32(data, 12 + 4 * index)? as i32;
+                *parameter = f64::from(fixed) / 65536.0;
+            }
+            if function > 0 && parameters[1] == 0.0 {
+                return Err("has a parametric curve with a = 0".to_string());
+            }
+            Ok(Trc::Parametric {
+                function,
+                parameters,
+            })
+        }
+        _ => Err(format!(
+            "has type '{}', expected 'curv' or 'para'",
+            String::from_utf8_lossy(tag_type)
+        )),
+    }
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, length: usize) -> Result<&[u8], String> {
+    offset
+        .checked_add(length)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| "is truncated".to_string())
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    let data = read_bytes(bytes, offset, 2)?;
+    Ok(u16::from_be_bytes([data[0], data[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    let data = read_bytes(bytes, offset, 4)?;
+    Ok(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal profile holding `tags`, laid out as ICC.1 requires.
+    fn profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = vec![0; HEADER_SIZE];
+        bytes[36..40].copy_from_slice(b"acsp");
+        bytes.extend(u32::try_from(tags.len()).unwrap().to_be_bytes());
+        let mut offset = HEADER_SIZE + 4 + tags.len() * TAG_ENTRY_SIZE;
+        let mut data: Vec<u8> = Vec::new();
+        for (signature, tag) in tags {
+            bytes.extend(*signature);
+            bytes.extend(u32::try_from(offset).unwrap().to_be_bytes());
+            bytes.extend(u32::try_from(tag.len()).unwrap().to_be_bytes());
+            data.extend(tag);
+            offset += tag.len();
+        }
+        bytes.extend(data);
+        let size = u32::try_from(bytes.len()).unwrap();
+        bytes[0..4].copy_from_slice(&size.to_be_bytes());
+        bytes
+    }
+
+    fn curv(entries: &[u16]) -> Vec<u8> {
+        let mut tag = b"curv\0\0\0\0".to_vec();
+        tag.extend(u32::try_from(entries.len()).unwrap().to_be_bytes());
+        for entry in entries {
+            tag.extend(entry.to_be_bytes());
+        }
+        tag
+    }
+
+    fn para(function: u16, parameters: &[f64]) -> Vec<u8> {
+        let mut tag = b"para\0\0\0\0".to_vec();
+        tag.extend(function.to_be_bytes());
+        tag.extend([0, 0]);
+        for parameter in parameters {
+            #[allow(clippy::cast_possible_truncation)]
+            tag.extend(((parameter * 65536.0).round() as i32).to_be_bytes());
+        }
+        tag
+    }
+
+    fn curve(trc: Trc) -> IccCurve {
+        IccCurve {
+            trc,
+            source: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let srgb = [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.040_45];
+        let bytes = profile(&[
+            (b"desc", b"desc\0\0\0\0".to_vec()),
+            (b"rTRC", curv(&[563])),
+            (b"gTRC", para(3, &srgb)),
+            (b"bTRC", curv(&[0, 16384, 65535])),
+        ]);
+        let curves = parse_profile(&bytes).unwrap();
+        assert_eq!(curves.len(), 3);
+        assert_eq!(curves[0], (Channel::Red, Trc::Gamma(563.0 / 256.0)));
+
+        // The sRGB parametric curve, to the precision of s15Fixed16
+        let green = curve(curves[1].1.clone());
+        assert!((green.to_linear(0.5) - 0.214_041).abs() < 1e-4);
+        assert!((green.to_linear(0.02) - 0.02 / 12.92).abs() < 1e-5);
+
+        let blue = curve(curves[2].1.clone());
+        assert!((blue.to_linear(0.25) - 0.125).abs() < 1e-4);
+        assert!((blue.to_linear(1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_decoding() {
+        let gamma = curve(Trc::Gamma(2.2));
+        assert!(gamma.validate_decoding().is_ok());
+        for i in 0..=20 {
+            let x = f64::from(i) / 20.0;
+            assert!((gamma.to_device(gamma.to_linear(x)) - x).abs() < 1e-9);
+        }
+
+        // Type 2 starts above zero, so darker light decodes to zero
+        let offset = curve(Trc::Parametric {
+            function: 2,
+            parameters: [1.0, 1.0, 0.0, 0.1, 0.0, 0.0, 0.0],
+        });
+        assert!((offset.to_linear(0.0) - 0.1).abs() < 1e-12);
+        assert!(offset.to_device(0.05).abs() < 1e-12);
+        assert!((offset.to_device(0.6) - 0.5).abs() < 1e-9);
+
+        let falling = curve(Trc::Sampled(vec![0.0, 0.6, 0.4, 1.0]));
+        assert!(falling.validate_decoding().is_err());
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(parse_profile(&[0; 64]).is_err());
+        assert!(parse_profile(&[0; 256]).is_err());
+        assert!(parse_profile(&profile(&[(b"desc", b"desc".to_vec())])).is_err());
+        assert!(parse_profile(&profile(&[(b"rTRC", b"sf32\0\0\0\0".to_vec())])).is_err());
+        assert!(parse_profile(&profile(&[(b"rTRC", para(5, &[1.0]))])).is_err());
+
+        // A curve that claims more entries than the tag holds
+        let mut truncated = curv(&[0, 65535]);
+        truncated[11] = 3;
+        let error = parse_profile(&profile(&[(b"gTRC", truncated)])).unwrap_err();
+        assert_eq!(error, "the gTRC tag is truncated");
+
+        // A profile that declares more bytes than it has
+        let mut short = profile(&[(b"kTRC", curv(&[256]))]);
+        short.truncate(short.len() - 1);
+        assert!(parse_profile(&short).is_err());
+    }
+}
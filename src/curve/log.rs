@@ -0,0 +1,272 @@
+//! Camera and ACES log encodings, with the constants published by each vendor.
+//!
+//! Scene-linear values produced by these curves exceed `1.0` at the top of the code range, so
+//! [`Curve::CameraLog`](super::Curve::CameraLog) normalizes linear light by the value at code
+//! `1.0` to keep the whole code range inside the table. [`AcesLog`] keeps ACES units instead,
+//! since negative and above-white values are part of its definition.
+
+/// Cineon printing density: 1023 code values, 0.002 density per code, 0.6 negative gamma.
+const CINEON_WHITE: f64 = 685.0;
+const CINEON_BLACK: f64 = 95.0;
+const CINEON_CODES_PER_DECADE: f64 = 300.0;
+
+/// Sony S-Log3 constants, in 10-bit code values.
+const SLOG3_CUT: f64 = 171.210_294_692_9;
+const SLOG3_LINEAR_CUT: f64 = 0.011_25;
+
+/// ARRI Log C (v3) constants for EI 800.
+const LOGC3_CUT: f64 = 0.010_591;
+const LOGC3_A: f64 = 5.555_556;
+const LOGC3_B: f64 = 0.052_272;
+const LOGC3_C: f64 = 0.247_190;
+const LOGC3_D: f64 = 0.385_537;
+const LOGC3_E: f64 = 5.367_655;
+const LOGC3_F: f64 = 0.092_809;
+
+/// Panasonic V-Log constants.
+const VLOG_LINEAR_CUT: f64 = 0.01;
+const VLOG_CUT: f64 = 0.181;
+const VLOG_B: f64 = 0.008_73;
+const VLOG_C: f64 = 0.241_514;
+const VLOG_D: f64 = 0.598_206;
+
+/// Canon Log (original) constants.
+const CLOG_SCALE: f64 = 0.529_136;
+const CLOG_GAIN: f64 = 10.1596;
+const CLOG_OFFSET: f64 = 0.073_059_7;
+
+/// `ACEScc` and `ACEScct` scale: `(log2(linear) + 9.72) / 17.52`.
+const ACES_LOG_OFFSET: f64 = 9.72;
+const ACES_LOG_SCALE: f64 = 17.52;
+/// Largest finite ACES value (the largest half-float).
+const ACES_MAX: f64 = 65_504.0;
+/// `ACEScct` toe: linear segment below this linear value.
+const ACESCCT_X_BREAK: f64 = 0.007_812_5;
+/// `ACEScct` toe: linear segment below this code value.
+const ACESCCT_Y_BREAK: f64 = 0.155_251_141_552_511;
+const ACESCCT_A: f64 = 10.540_237_741_654_5;
+const ACESCCT_B: f64 = 0.072_905_534_195_835_5;
+
+/// A vendor log encoding selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CameraLog {
+    /// Kodak Cineon printing density.
+    Cineon,
+    /// Sony S-Log3.
+    SLog3,
+    /// ARRI Log C (v3) at EI 800.
+    LogC3,
+    /// Panasonic V-Log.
+    VLog,
+    /// Canon Log.
+    CLog,
+}
+
+impl CameraLog {
+    /// Names accepted by the `curve` parameter, in the same order as [`CameraLog::from_name`].
+    pub(crate) const NAMES: [&'static str; 5] = ["cineon", "slog3", "logc3", "vlog", "clog"];
+
+    pub(crate) fn from_name(name: &str) -> Option<CameraLog> {
+        match name {
+            "cineon" => Some(CameraLog::Cineon),
+            "slog3" => Some(CameraLog::SLog3),
+            "logc3" => Some(CameraLog::LogC3),
+            "vlog" => Some(CameraLog::VLog),
+            "clog" => Some(CameraLog::CLog),
+            _ => None,
+        }
+    }
+
+    /// Log code value in `0.0..=1.0` to scene-linear light.
+    pub(crate) fn to_linear(self, code: f64) -> f64 {
+        match self {
+            CameraLog::Cineon => {
+                let black = cineon_black_offset();
+                (10_f64.powf((code * 1023.0 - CINEON_WHITE) / CINEON_CODES_PER_DECADE) - black)
+                    / (1.0 - black)
+            }
+            CameraLog::SLog3 => {
+                let cv = code * 1023.0;
+                if cv >= SLOG3_CUT {
+                    10_f64.powf((cv - 420.0) / 261.5) * 0.19 - 0.01
+                } else {
+                    (cv - 95.0) * SLOG3_LINEAR_CUT / (SLOG3_CUT - 95.0)
+                }
+            }
+            CameraLog::LogC3 => {
+                if code > LOGC3_E * LOGC3_CUT + LOGC3_F {
+                    (10_f64.powf((code - LOGC3_D) / LOGC3_C) - LOGC3_B) / LOGC3_A
+                } else {
+                    (code - LOGC3_F) / LOGC3_E
+                }
+            }
+            CameraLog::VLog => {
+                if code < VLOG_CUT {
+                    (code - 0.125) / 5.6
+                } else {
+                    10_f64.powf((code - VLOG_D) / VLOG_C) - VLOG_B
+                }
+            }
+            CameraLog::CLog => {
+                if code >= CLOG_OFFSET {
+                    (10_f64.powf((code - CLOG_OFFSET) / CLOG_SCALE) - 1.0) / CLOG_GAIN
+                } else {
+                    -(10_f64.powf((CLOG_OFFSET - code) / CLOG_SCALE) - 1.0) / CLOG_GAIN
+                }
+            }
+        }
+    }
+
+    /// Scene-linear light to log code value in `0.0..=1.0`.
+    pub(crate) fn to_log(self, linear: f64) -> f64 {
+        match self {
+            CameraLog::Cineon => {
+                let black = cineon_black_offset();
+                (CINEON_WHITE + CINEON_CODES_PER_DECADE * (linear * (1.0 - black) + black).log10())
+                    / 1023.0
+            }
+            CameraLog::SLog3 => {
+                if linear >= SLOG3_LINEAR_CUT {
+                    (420.0 + ((linear + 0.01) / 0.19).log10() * 261.5) / 1023.0
+                } else {
+                    (linear * (SLOG3_CUT - 95.0) / SLOG3_LINEAR_CUT + 95.0) / 1023.0
+                }
+            }
+            CameraLog::LogC3 => {
+                if linear > LOGC3_CUT {
+                    LOGC3_C * (LOGC3_A * linear + LOGC3_B).log10() + LOGC3_D
+                } else {
+                    LOGC3_E * linear + LOGC3_F
+                }
+            }
+            CameraLog::VLog => {
+                if linear < VLOG_LINEAR_CUT {
+                    5.6 * linear + 0.125
+                } else {
+                    VLOG_C * (linear + VLOG_B).log10() + VLOG_D
+                }
+            }
+            CameraLog::CLog => {
+                if linear >= 0.0 {
+                    CLOG_SCALE * (CLOG_GAIN * linear + 1.0).log10() + CLOG_OFFSET
+                } else {
+                    -CLOG_SCALE * (-CLOG_GAIN * linear + 1.0).log10() + CLOG_OFFSET
+                }
+            }
+        }
+    }
+}
+
+/// An ACES log encoding selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AcesLog {
+    /// `ACEScc` (S-2014-003), pure log with special handling of values at and below zero.
+    Cc,
+    /// `ACEScct` (S-2016-001), `ACEScc` with a linear toe near black.
+    Cct,
+}
+
+impl AcesLog {
+    /// Names accepted by the `curve` parameter, in the same order as [`AcesLog::from_name`].
+    pub(crate) const NAMES: [&'static str; 2] = ["acescc", "acescct"];
+
+    pub(crate) fn from_name(name: &str) -> Option<AcesLog> {
+        match name {
+            "acescc" => Some(AcesLog::Cc),
+            "acescct" => Some(AcesLog::Cct),
+            _ => None,
+        }
+    }
+
+    /// ACES log code value to ACES linear.
+    pub(crate) fn to_linear(self, code: f64) -> f64 {
+        let max_code = (ACES_MAX.log2() + ACES_LOG_OFFSET) / ACES_LOG_SCALE;
+        match self {
+            AcesLog::Cc if code < (ACES_LOG_OFFSET - 15.0) / ACES_LOG_SCALE => {
+                ((code * ACES_LOG_SCALE - ACES_LOG_OFFSET).exp2() - (-16_f64).exp2()) * 2.0
+            }
+            AcesLog::Cct if code <= ACESCCT_Y_BREAK => (code - ACESCCT_B) / ACESCCT_A,
+            _ if code >= max_code => ACES_MAX,
+            _ => (code * ACES_LOG_SCALE - ACES_LOG_OFFSET).exp2(),
+        }
+    }
+
+    /// ACES linear to ACES log code value.
+    pub(crate) fn to_log(self, linear: f64) -> f64 {
+        match self {
+            AcesLog::Cc if linear <= 0.0 => (-16.0 + ACES_LOG_OFFSET) / ACES_LOG_SCALE,
+            AcesLog::Cc if linear < (-15_f64).exp2() => {
+                (((-16_f64).exp2() + linear * 0.5).log2() + ACES_LOG_OFFSET) / ACES_LOG_SCALE
+            }
+            AcesLog::Cct if linear <= ACESCCT_X_BREAK => ACESCCT_A * linear + ACESCCT_B,
+            _ => (linear.log2() + ACES_LOG_OFFSET) / ACES_LOG_SCALE,
+        }
+    }
+}
+
+/// Linear light of Cineon code 0 relative to white, subtracted so that code 95 is black.
+fn cineon_black_offset() -> f64 {
+    10_f64.powf((CINEON_BLACK - CINEON_WHITE) / CINEON_CODES_PER_DECADE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for name in CameraLog::NAMES {
+            let log = CameraLog::from_name(name).unwrap();
+            for i in 0..=100 {
+                let code = f64::from(i) / 100.0;
+                let round_trip = log.to_log(log.to_linear(code));
+                assert!((round_trip - code).abs() < 1e-9, "{name} at {code}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_aces_round_trip() {
+        for name in AcesLog::NAMES {
+            let log = AcesLog::from_name(name).unwrap();
+            for i in -20..=100 {
+                let code = f64::from(i) / 100.0;
+                // ACEScc cannot encode values at or below zero, which its lowest codes decode to
+                if log == AcesLog::Cc && log.to_linear(code) <= 0.0 {
+                    continue;
+                }
+                let round_trip = log.to_log(log.to_linear(code));
+                assert!((round_trip - code).abs() < 1e-9, "{name} at {code}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_aces_known_values() {
+        // 18% grey is 0.4136 in both encodings, they differ only near black
+        assert!((AcesLog::Cc.to_log(0.18) - 0.413_588).abs() < 1e-6);
+        assert!((AcesLog::Cct.to_log(0.18) - 0.413_588).abs() < 1e-6);
+        assert!((AcesLog::Cc.to_log(0.0) + 0.358_447).abs() < 1e-6);
+        assert!((AcesLog::Cct.to_log(0.0) - ACESCCT_B).abs() < 1e-12);
+        // The ACEScct toe meets the log segment at the break point
+        let toe = ACESCCT_A * ACESCCT_X_BREAK + ACESCCT_B;
+        let log = (ACESCCT_X_BREAK.log2() + ACES_LOG_OFFSET) / ACES_LOG_SCALE;
+        assert!((toe - ACESCCT_Y_BREAK).abs() < 1e-12);
+        assert!((log - ACESCCT_Y_BREAK).abs() < 1e-12);
+        // Codes above the half-float range saturate
+        assert!((AcesLog::Cc.to_linear(1.5) - ACES_MAX).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_published_mid_grey() {
+        // 18% grey code values from each vendor's documentation
+        assert!((CameraLog::SLog3.to_log(0.18) * 1023.0 - 420.0).abs() < 1e-9);
+        assert!((CameraLog::LogC3.to_log(0.18) - 0.391_007).abs() < 1e-6);
+        assert!((CameraLog::VLog.to_log(0.18) - 0.423_311).abs() < 1e-6);
+        assert!((CameraLog::CLog.to_log(0.18 / 0.9) - 0.327_954).abs() < 1e-6);
+
+        // Cineon: code 95 is black and code 685 is white
+        assert!(CameraLog::Cineon.to_linear(95.0 / 1023.0).abs() < 1e-12);
+        assert!((CameraLog::Cineon.to_linear(685.0 / 1023.0) - 1.0).abs() < 1e-12);
+    }
+}
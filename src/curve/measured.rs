@@ -0,0 +1,358 @@
+//! Curves interpolated through measured calibration points.
+//!
+//! The points are normalized so that the first one maps to `(0.0, 0.0)` and the last to
+//! `(1.0, 1.0)`, which lets colorimeter readings in cd/m² be used as they are. Inputs outside
+//! the measured range take the value of the nearest end point.
+
+use super::read_manifest_file;
+use proc_macro2::Span;
+use std::path::{Path, PathBuf};
+use syn::{Error, LitStr};
+
+/// Iterations of the bisection that inverts one cubic segment, enough for `f64` precision.
+const SEGMENT_ITERATIONS: u32 = 64;
+
+/// How values between calibration points are filled in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Interpolation {
+    /// Straight lines between the points.
+    Linear,
+    /// Fritsch–Carlson monotone cubic Hermite splines, smooth without overshooting the points.
+    Monotone,
+}
+
+impl Interpolation {
+    /// Names accepted by the `interpolation` parameter.
+    pub(crate) const NAMES: [&'static str; 2] = ["linear", "monotone"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Interpolation> {
+        match name {
+            "linear" => Some(Interpolation::Linear),
+            "monotone" => Some(Interpolation::Monotone),
+            _ => None,
+        }
+    }
+}
+
+/// Calibration points as given with the `points` parameter, before normalization.
+#[derive(Debug, Clone)]
+pub(crate) struct Points {
+    /// Where errors about the points are reported: the inline list or the path literal.
+    pub(crate) span: Span,
+    pub(crate) values: Vec<(f64, f64)>,
+    /// The CSV file the points were read from, which the generated code depends on.
+    pub(crate) source: Option<PathBuf>,
+}
+
+impl Points {
+    /// Reads `input,output` rows from a CSV file at `path`, relative to `CARGO_MANIFEST_DIR`.
+    ///
+    /// Blank lines and lines starting with `#` are skipped, as is a header row of column
+    /// names if it is the first line that is neither.
+    pub(crate) fn from_csv(path: &LitStr) -> syn::Result<Points> {
+        let span = path.span();
+        let (source, bytes) = read_manifest_file(path)?;
+        let values = String::from_utf8(bytes)
+            .map_err(|_| "the file is not UTF-8 text".to_string())
+            .and_then(|text| parse_csv(&text))
+            .map_err(|message| {
+                Error::new(
+                    span,
+                    format!(
+                        "{} is not a valid calibration CSV: {message}",
+                        source.display()
+                    ),
+                )
+            })?;
+        Ok(Points {
+            span,
+            values,
+            source: Some(source),
+        })
+    }
+}
+
+/// Reads the `input,output` rows of a calibration CSV file, as described for
+/// [`Points::from_csv`].
+fn parse_csv(text: &str) -> Result<Vec<(f64, f64)>, String> {
+    let mut values = Vec::new();
+    let mut first_row = true;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let point = match fields[..] {
+            [input, output] => input.parse::<f64>().ok().zip(output.parse::<f64>().ok()),
+            _ => None,
+        };
+        // A header names the columns instead of holding numbers
+        let header = first_row
+            && fields.len() == 2
+            && fields.iter().all(|field| field.parse::<f64>().is_err());
+        first_row = false;
+        match point {
+            Some(point) => values.push(point),
+            None if header => {}
+            None => {
+                return Err(format!(
+                    "line {} is not an `input,output` pair of numbers: {line}",
+                    number + 1
+                ))
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// A curve through calibration points, in the unit square.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Measured {
+    points: Vec<(f64, f64)>,
+    /// Slope at each point, for [`Interpolation::Monotone`].
+    tangents: Vec<f64>,
+    interpolation: Interpolation,
+    source: Option<PathBuf>,
+}
+
+impl Measured {
+    /// Checks and normalizes `points`, returning a message describing the first problem.
+    pub(crate) fn new(points: Points, interpolation: Interpolation) -> Result<Measured, String> {
+        let Points { values, source, .. } = points;
+        if values.len() < 2 {
+            return Err(format!(
+                "At least 2 calibration points are needed (got {})",
+                values.len()
+            ));
+        }
+        if let Some((input, output)) = values
+            .iter()
+            .find(|(input, output)| !input.is_finite() || !output.is_finite())
+        {
+            return Err(format!(
+                "Calibration point ({input}, {output}) is not finite"
+            ));
+        }
+        if let Some(pair) = values.windows(2).find(|pair| pair[1].0 <= pair[0].0) {
+            return Err(format!(
+                "Calibration inputs must strictly increase, but {} is followed by {}",
+                pair[0].0, pair[1].0
+            ));
+        }
+        if let Some(pair) = values.windows(2).find(|pair| pair[1].1 < pair[0].1) {
+            return Err(format!(
+                "Calibration outputs must not decrease, but {} at input {} is followed by {} at input {}",
+                pair[0].1, pair[0].0, pair[1].1, pair[1].0
+            ));
+        }
+
+        let (first, last) = (values[0], values[values.len() - 1]);
+        if last.1 <= first.1 {
+            return Err("Calibration outputs must not all be the same".to_string());
+        }
+        let points: Vec<(f64, f64)> = values
+            .iter()
+            .map(|(input, output)| {
+                (
+                    (input - first.0) / (last.0 - first.0),
+                    (output - first.1) / (last.1 - first.1),
+                )
+            })
+            .collect();
+        let tangents = match interpolation {
+            Interpolation::Linear => Vec::new(),
+            Interpolation::Monotone => fritsch_carlson_tangents(&points),
+        };
+        Ok(Measured {
+            points,
+            tangents,
+            interpolation,
+            source,
+        })
+    }
+
+    /// The CSV file the points were read from, if any.
+    pub(crate) fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Normalized input to normalized output.
+    pub(crate) fn to_output(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        // The last segment that starts at or before x
+        let index = self.points[1..].partition_point(|point| point.0 <= x);
+        let index = index.min(self.points.len() - 2);
+        let (x0, x1) = (self.points[index].0, self.points[index + 1].0);
+        self.segment(index, (x - x0) / (x1 - x0))
+    }
+
+    /// Normalized output to the lowest normalized input that produces it.
+    pub(crate) fn to_input(&self, y: f64) -> f64 {
+        let y = y.clamp(0.0, 1.0);
+        // The first segment that ends at or above y
+        let index = self.points[1..].partition_point(|point| point.1 < y);
+        let index = index.min(self.points.len() - 2);
+        let (x0, x1) = (self.points[index].0, self.points[index + 1].0);
+        let t = match self.interpolation {
+            Interpolation::Linear => {
+                let (y0, y1) = (self.points[index].1, self.points[index + 1].1);
+                if y1 > y0 {
+                    (y - y0) / (y1 - y0)
+                } else {
+                    0.0
+                }
+            }
+            // Each segment is monotone, so bisection finds the single crossing
+            Interpolation::Monotone => {
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..SEGMENT_ITERATIONS {
+                    let middle = f64::midpoint(low, high);
+                    if self.segment(index, middle) < y {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                high
+            }
+        };
+        x0 + t * (x1 - x0)
+    }
+
+    /// Evaluates segment `index` at `t`, the fraction of the way to the next point.
+    fn segment(&self, index: usize, t: f64) -> f64 {
+        let (x0, y0) = self.points[index];
+        let (x1, y1) = self.points[index + 1];
+        match self.interpolation {
+            Interpolation::Linear => y0 + t * (y1 - y0),
+            Interpolation::Monotone => {
+                // Cubic Hermite basis
+                let h = x1 - x0;
+                let t2 = t * t;
+                let t3 = t2 * t;
+                (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                    + (t3 - 2.0 * t2 + t) * h * self.tangents[index]
+                    + (-2.0 * t3 + 3.0 * t2) * y1
+                    + (t3 - t2) * h * self.tangents[index + 1]
+            }
+        }
+    }
+}
+
+/// Tangents for a cubic Hermite spline through non-decreasing `points` that never decreases,
+/// following Fritsch and Carlson (1980).
+fn fritsch_carlson_tangents(points: &[(f64, f64)]) -> Vec<f64> {
+    let secants: Vec<f64> = points
+        .windows(2)
+        .map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+        .collect();
+
+    // Start from the average of the neighbouring secants, one-sided at the ends
+    let mut tangents = Vec::with_capacity(points.len());
+    tangents.push(secants[0]);
+    for pair in secants.windows(2) {
+        tangents.push(f64::midpoint(pair[0], pair[1]));
+    }
+    tangents.push(secants[secants.len() - 1]);
+
+    // Flat segments stay flat, and steep tangents are scaled back into the monotone region
+    for (index, &secant) in secants.iter().enumerate() {
+        if secant == 0.0 {
+            tangents[index] = 0.0;
+            tangents[index + 1] = 0.0;
+            continue;
+        }
+        let alpha = tangents[index] / secant;
+        let beta = tangents[index + 1] / secant;
+        let magnitude = alpha.hypot(beta);
+        if magnitude > 3.0 {
+            let tau = 3.0 / magnitude;
+            tangents[index] = tau * alpha * secant;
+            tangents[index + 1] = tau * beta * secant;
+        }
+    }
+    tangents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measured(values: &[(f64, f64)], interpolation: Interpolation) -> Result<Measured, String> {
+        let points = Points {
+            span: Span::call_site(),
+            values: values.to_vec(),
+            source: None,
+        };
+        Measured::new(points, interpolation)
+    }
+
+    /// Readings of an LED with a visible plateau near the top, in PWM duty and cd/m².
+    const LED: [(f64, f64); 7] = [
+        (0.0, 0.5),
+        (32.0, 4.0),
+        (64.0, 20.0),
+        (128.0, 90.0),
+        (192.0, 200.0),
+        (224.0, 201.0),
+        (255.0, 201.0),
+    ];
+
+    #[test]
+    fn test_passes_through_points() {
+        for interpolation in [Interpolation::Linear, Interpolation::Monotone] {
+            let curve = measured(&LED, interpolation).unwrap();
+            for &(x, y) in &curve.points {
+                assert!((curve.to_output(x) - y).abs() < 1e-12, "{interpolation:?}");
+                assert!((curve.to_output(curve.to_input(y)) - y).abs() < 1e-9);
+            }
+        }
+        // Normalized to the first and last points
+        let curve = measured(&LED, Interpolation::Linear).unwrap();
+        assert!(curve.to_output(0.0).abs() < 1e-12);
+        assert!((curve.to_output(1.0) - 1.0).abs() < 1e-12);
+        assert!((curve.to_output(64.0 / 255.0) - 19.5 / 200.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_monotone_never_decreases() {
+        let curve = measured(&LED, Interpolation::Monotone).unwrap();
+        let samples: Vec<f64> = (0..=1000)
+            .map(|i| curve.to_output(f64::from(i) / 1000.0))
+            .collect();
+        assert!(samples.windows(2).all(|pair| pair[1] >= pair[0]));
+        // The plateau between the last two points stays flat instead of overshooting
+        assert!(samples.iter().all(|value| *value <= 1.0));
+        assert!((curve.to_output(240.0 / 255.0) - 1.0).abs() < 1e-12);
+        // Inputs on the plateau decode to where it starts
+        assert!((curve.to_input(1.0) - 224.0 / 255.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let values = parse_csv("# LED at 25 °C\n\nduty,cd/m2\n0,0.5\n 128 , 90 \n").unwrap();
+        assert_eq!(values, [(0.0, 0.5), (128.0, 90.0)]);
+        assert_eq!(parse_csv("0,0.5\n255,201\n").unwrap().len(), 2);
+
+        // Only the first row may be a header, and only if it holds no numbers
+        let error = parse_csv("1,abc\n0,0.5\n").unwrap_err();
+        assert_eq!(
+            error,
+            "line 1 is not an `input,output` pair of numbers: 1,abc"
+        );
+        assert!(parse_csv("duty,cd/m2\nlevel,luminance\n0,0.5\n").is_err());
+        assert!(parse_csv("0,0.5\nduty,cd/m2\n").is_err());
+        assert!(parse_csv("duty,luminance,notes\n0,0.5\n").is_err());
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        let linear = Interpolation::Linear;
+        assert!(measured(&[(0.0, 0.0)], linear).is_err());
+        assert!(measured(&[(0.0, 0.0), (0.0, 1.0)], linear).is_err());
+        assert!(measured(&[(0.0, 0.0), (0.5, 0.6), (1.0, 0.5)], linear).is_err());
+        assert!(measured(&[(0.0, 1.0), (1.0, 1.0)], linear).is_err());
+        assert!(measured(&[(0.0, 0.0), (1.0, f64::INFINITY)], linear).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Pipelines that run the table input through several curves and linear adjustments.
+//!
+//! Every stage works on the `f64` output of the one before it, so the result is only
+//! quantized once, when [`generate_table_values`](crate::generate_table_values) scales it.
+
+use super::Curve;
+use proc_macro2::Span;
+use syn::Error;
+
+/// One step of a [`Curve::Pipeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Stage {
+    /// A curve evaluated in its own direction.
+    Curve { curve: Curve, decoding: bool },
+    /// `input * gain + offset`.
+    Affine { gain: f64, offset: f64 },
+}
+
+impl Stage {
+    /// The `from_gamma`/`to_gamma` shorthand: decode with one power law, encode with another.
+    pub(crate) fn transcode(from_gamma: f64, to_gamma: f64) -> Vec<Stage> {
+        vec![
+            Stage::Curve {
+                curve: Curve::Power { gamma: from_gamma },
+                decoding: false,
+            },
+            Stage::Curve {
+                curve: Curve::Power { gamma: to_gamma },
+                decoding: true,
+            },
+        ]
+    }
+
+    /// Checks the stage on its own, as [`generate_gamma_table`](crate::generate_gamma_table)
+    /// checks a whole table's curve.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let Stage::Curve { curve, decoding } = self else {
+            return Ok(());
+        };
+        curve.validate()?;
+        if *decoding {
+            curve.validate_decoding()?;
+        }
+        curve.validate_inverse(*decoding)?;
+        if curve.has_sign_bit() {
+            return Err("Companding curves cannot be used in a pipeline".to_string());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply(&self, x: f64) -> f64 {
+        match self {
+            Stage::Curve { curve, decoding } => curve.eval(x, *decoding),
+            Stage::Affine { gain, offset } => x * gain + offset,
+        }
+    }
+}
+
+/// Runs `x` through `stages`, reporting the first stage whose output is not finite. Errors
+/// inside an `expr` stage point into the expression, all others at `span`.
+pub(crate) fn try_apply(stages: &[Stage], x: f64, span: Span) -> syn::Result<f64> {
+    stages
+        .iter()
+        .enumerate()
+        .try_fold(x, |value, (index, stage)| {
+            if let Stage::Curve {
+                curve: Curve::Expression(expression),
+                ..
+            } = stage
+            {
+                return expression.try_eval(value);
+            }
+            let output = stage.apply(value);
+            if output.is_finite() {
+                Ok(output)
+            } else {
+                Err(Error::new(
+                    span,
+                    format!(
+                        "Stage {} of the pipeline evaluates to {output} at x = {x}",
+                        index + 1
+                    ),
+                ))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcode() {
+        let stages = Stage::transcode(2.2, 2.5);
+        let value = stages.iter().fold(0.5, |value, stage| stage.apply(value));
+        assert!((value - 0.5_f64.powf(2.2 / 2.5)).abs() < 1e-12);
+        assert!(stages.iter().all(|stage| stage.validate().is_ok()));
+    }
+
+    #[test]
+    fn test_try_apply() {
+        let stages = [
+            Stage::Affine {
+                gain: 2.0,
+                offset: -1.0,
+            },
+            Stage::Curve {
+                curve: Curve::Power { gamma: 0.5 },
+                decoding: false,
+            },
+        ];
+        assert!((try_apply(&stages, 1.0, Span::call_site()).unwrap() - 1.0).abs() < 1e-12);
+        // A negative value through a square root
+        let error = try_apply(&stages, 0.0, Span::call_site()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Stage 2 of the pipeline evaluates to NaN at x = 0"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Tone-mapping operators that compress scene-linear HDR light into the display range.
+//!
+//! Each operator maps linear light in `0.0..=white` to display-linear light in `0.0..=1.0`;
+//! [`Curve::ToneMap`](super::Curve::ToneMap) scales the normalized table input by `white`.
+
+/// Uncharted 2 filmic curve constants from John Hable's presentation: shoulder strength,
+/// linear strength, linear angle, toe strength, toe numerator and toe denominator.
+const HABLE_A: f64 = 0.15;
+const HABLE_B: f64 = 0.50;
+const HABLE_C: f64 = 0.10;
+const HABLE_D: f64 = 0.20;
+const HABLE_E: f64 = 0.02;
+const HABLE_F: f64 = 0.30;
+/// Linear white point used by Hable when none is given.
+pub(crate) const HABLE_WHITE: f64 = 11.2;
+
+/// Stephen Hill's rational fit of the ACES RRT and sRGB ODT.
+const ACES_A: f64 = 0.024_578_6;
+const ACES_B: f64 = 0.000_090_537;
+const ACES_C: f64 = 0.983_729;
+const ACES_D: f64 = 0.432_951;
+const ACES_E: f64 = 0.238_081;
+
+/// A tone-mapping operator selected by name with the `curve` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ToneMap {
+    /// Extended Reinhard, `L (1 + L / white²) / (1 + L)`, which maps `white` to `1.0`.
+    Reinhard,
+    /// Hable's Uncharted 2 filmic curve, normalized so that `white` maps to `1.0`.
+    Hable,
+    /// Hill's fit of the ACES RRT and ODT, applied per channel without the color matrices.
+    AcesFitted,
+}
+
+impl ToneMap {
+    /// Names accepted by the `curve` parameter, in the same order as [`ToneMap::from_name`].
+    pub(crate) const NAMES: [&'static str; 3] = ["reinhard", "hable", "aces_fitted"];
+
+    pub(crate) fn from_name(name: &str) -> Option<ToneMap> {
+        match name {
+            "reinhard" => Some(ToneMap::Reinhard),
+            "hable" => Some(ToneMap::Hable),
+            "aces_fitted" => Some(ToneMap::AcesFitted),
+            _ => None,
+        }
+    }
+
+    /// Scene-linear `light` to display-linear light, with `white` the largest input.
+    pub(crate) fn apply(self, light: f64, white: f64) -> f64 {
+        match self {
+            ToneMap::Reinhard => light * (1.0 + light / (white * white)) / (1.0 + light),
+            ToneMap::Hable => hable(light) / hable(white),
+            // The fit dips slightly below zero at black and levels off just above one
+            ToneMap::AcesFitted => {
+                let numerator = light * (light + ACES_A) - ACES_B;
+                let denominator = light * (ACES_C * light + ACES_D) + ACES_E;
+                (numerator / denominator).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+/// Hable's unnormalized filmic curve.
+fn hable(x: f64) -> f64 {
+    (x * (HABLE_A * x + HABLE_C * HABLE_B) + HABLE_D * HABLE_E)
+        / (x * (HABLE_A * x + HABLE_B) + HABLE_D * HABLE_F)
+        - HABLE_E / HABLE_F
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_white_point() {
+        assert!((ToneMap::Reinhard.apply(4.0, 4.0) - 1.0).abs() < 1e-12);
+        assert!((ToneMap::Hable.apply(HABLE_WHITE, HABLE_WHITE) - 1.0).abs() < 1e-12);
+        for operator in [ToneMap::Reinhard, ToneMap::Hable, ToneMap::AcesFitted] {
+            assert!(operator.apply(0.0, 4.0).abs() < 1e-12, "{operator:?}");
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        // Plain Reinhard at 1.0 is one half, the white point lifts it slightly
+        assert!((ToneMap::Reinhard.apply(1.0, 4.0) - 0.531_25).abs() < 1e-12);
+        // Hill's fit darkens 18% grey to about 0.106 display-linear and saturates highlights
+        assert!((ToneMap::AcesFitted.apply(0.18, 16.0) - 0.105_591).abs() < 1e-6);
+        assert!(ToneMap::AcesFitted.apply(16.0, 16.0) > 0.97);
+    }
+}
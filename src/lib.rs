@@ -21,9 +21,12 @@
 #![warn(clippy::pedantic)]
 extern crate proc_macro;
 
+mod curve;
+
+use curve::{format_cube, CubeLut, Curve, CurveParams, Expression, IccProfile, Points, Stage};
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt, LitStr};
 
 /// Generates a gamma lookup table as a procedural macro.
 ///
@@ -34,9 +37,65 @@ use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
 /// - `name`: `IDENT`\
 ///   The name of the generated constant table (e.g., `GAMMA_TABLE_22`).
 /// - `entry_type`: `Type`\
-///   The unsigned integer type for table entries (`u8`, `u16`, `u32`, or `u64`).
-/// - `gamma`: `float`\
-///   The gamma value to use for encoding or decoding. Must be positive.
+///   The integer type for table entries (`u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, or `i64`).
+///   Signed types are only needed for curves whose output can be negative.
+/// - `gamma`: `float` (required unless `curve` or `expr` is given)\
+///   The gamma value to use for encoding or decoding. Must be positive. Tone-mapping curves
+///   and `sigmoid` accept it as an optional display encoding stage.
+/// - `curve`: `IDENT` (optional)\
+///   Selects a named transfer curve instead of a pure power law (see [Curves](#curves)).
+/// - `expr`: `Expr` (optional)\
+///   A math expression in the normalized input `x` that defines the curve (see
+///   [Expressions](#expressions)). Selects the `expr` curve.
+/// - `stages`: `[{ ... }, ...]` (optional)\
+///   An ordered list of stages that are composed into one curve (see [Pipelines](#pipelines)).
+/// - `from_gamma`, `to_gamma`: `float` (optional)\
+///   Shorthand for a pipeline that decodes with `from_gamma` and encodes with `to_gamma`,
+///   `input ^ (from_gamma / to_gamma)`. Both are required when either is given.
+/// - `points`: `[(float, float), ...]` or `"path.csv"` (optional)\
+///   Measured `(input, output)` calibration points, inline or from a CSV file relative to
+///   `CARGO_MANIFEST_DIR`. Selects the `measured` curve.
+/// - `interpolation`: `IDENT` (optional, default `monotone`)\
+///   How the `measured` curve fills in between points: `monotone` or `linear`.
+/// - `icc_profile`: `"path.icc"` (optional)\
+///   An ICC profile, relative to `CARGO_MANIFEST_DIR`, whose tone response curves fill the
+///   table, or a table per channel for an RGB profile. Selects the `icc` curve.
+/// - `cube_lut`: `"path.cube"` (optional)\
+///   A 1D `.cube` LUT, relative to `CARGO_MANIFEST_DIR`, resampled to `size` entries. Selects
+///   the `cube` curve.
+/// - `wire_format`: `bool` (optional, default true)\
+///   Whether `mu_law` and `a_law` codes have the bits G.711 inverts on the wire (all bits for
+///   μ-law, the even bits for A-law) inverted. With `false`, codes are sign-magnitude: a sign
+///   bit (set for negative samples), a 3-bit segment and a 4-bit step, so they rise with the
+///   sample's magnitude.
+/// - `channel`: `IDENT` (optional; required for LUTs whose columns differ)\
+///   Which curve of the profile to use: `red`, `green` or `blue` (the `rTRC`, `gTRC` and `bTRC`
+///   tags), or `gray` (`kTRC`), which grayscale profiles select by default. Without it, an RGB
+///   profile generates a table per channel. For a `.cube` LUT, the red, green or blue column.
+/// - `fit`: `IDENT` (optional)\
+///   Fits a power law to `points` instead of interpolating them: `gamma` or
+///   `gamma_with_black` (see [Fitting](#fitting)).
+/// - `knee`, `slope`, `offset`, `power`: `float` (optional)\
+///   Parameters of a two-segment `piecewise` curve. Giving them without `curve` selects it.
+/// - `black_level`, `white_level`: `float` (optional, default `0.0` and `100.0`)\
+///   Screen luminance in cd/m² at black and white for the `bt1886` curve.
+/// - `peak_nits`: `float` (optional, default `10000.0`)\
+///   Luminance in cd/m² that maps to `max_value` for the `pq` curve.
+/// - `system_gamma`: `float` (optional, default `1.0`)\
+///   Exponent of the OOTF applied by the `hlg` curve. `1.0` leaves scene light unchanged.
+/// - `min_luminance`, `max_luminance`: `float` (required for `gsdf`)\
+///   Luminance in cd/m² of the display at its lowest and highest driving level.
+/// - `white`: `float` (required for `reinhard` and `aces_fitted`, default `11.2` for `hable`)\
+///   Linear light at the last table entry for tone-mapping curves.
+/// - `midpoint`, `steepness`: `float` (`steepness` required for `sigmoid`, `midpoint` default `0.5`)\
+///   Center and slope of the `sigmoid` contrast curve.
+/// - `expo`, `dead_zone`: `float` (`expo` required for `bipolar`, `dead_zone` default `0.0`)\
+///   Exponent of each half of the `bipolar` curve, and the fraction of each half around the
+///   center that outputs `0`.
+/// - `dot_gain`: `float` (required for `dot_gain`)\
+///   Measured gain of a 50% dot as a fraction, e.g. `0.18` when it prints as 68%.
+/// - `min_db`, `max_db`: `float` (optional, default `-60.0` and `0.0`)\
+///   Range of the `db_taper` curve. Negative values are written with a leading `-`.
 /// - `size`: `integer`\
 ///   The number of entries in the table. Must be at least 3.
 /// - `max_value`: `integer` (optional, default `size-1`)\
@@ -45,6 +104,13 @@ use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
 /// - `decoding`: `bool` (optional, default false)\
 ///   If `true`, generates a gamma correction (decoding) table using `input^(1/gamma)`.\
 ///   If `false` or omitted, generates a gamma encoding table using `input^gamma`.
+/// - `invert`: `bool` (optional, default false)\
+///   If `true`, generates the numerical inverse of the curve (in the direction selected by
+///   `decoding`), so that any monotonic curve gets a decode table. See [Inversion](#inversion).
+/// - `export_cube`: `bool` (optional, default false)\
+///   If `true`, also generates `<NAME>_CUBE`, a `&str` holding the table as a 1D `.cube` file
+///   for previewing in grading tools. Entries are divided by `max_value`, and the same curve
+///   fills all three columns.
 ///
 /// # Gamma Processing
 /// - **Gamma Encoding (default):**\
@@ -54,14 +120,225 @@ use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
 ///   `output = (input / max_input) ^ (1/gamma) * max_value`\
 ///   Makes mid-tones brighter, suitable for correcting gamma-encoded data.
 ///
+/// # Curves
+/// Each curve follows the same convention as the power law: the default direction maps an
+/// encoded signal to linear light (like `input^gamma`), and `decoding: true` maps linear light
+/// back to the encoded signal (like `input^(1/gamma)`).
+/// - `srgb`: The exact IEC 61966-2-1 sRGB transfer function, including its linear segment
+///   near black. The default direction converts sRGB-encoded values to linear light,
+///   `decoding: true` converts linear light to sRGB-encoded values.
+/// - `piecewise`: A linear segment below `knee` and an offset power law above it:\
+///   `output = input / slope` if `input <= knee`, else `((input + offset) / (1 + offset)) ^ power`.\
+///   All four parameters are required, and the two segments must meet at the knee
+///   (within one part in a million of the value there). `srgb` is this curve with `knee: 0.04045, slope: 12.92,
+///   offset: 0.055, power: 2.4`.
+/// - `rec709`: The ITU-R BT.709 OETF with its exact constants. The default direction converts
+///   the video signal back to scene light, `decoding: true` applies the OETF.
+/// - `rec2020_10bit`, `rec2020_12bit`: The ITU-R BT.2020 OETF with the constants for 10-bit
+///   and 12-bit systems, in the same directions as `rec709`.
+/// - `bt1886`: The ITU-R BT.1886 reference display EOTF, `L = a * max(V + b, 0) ^ 2.4`, with
+///   `a` and `b` derived from `black_level` (`Lb`) and `white_level` (`Lw`). The default
+///   direction converts the video signal to screen luminance relative to `white_level`;
+///   `decoding: true` converts relative luminance to the video signal.
+/// - `pq`: The SMPTE ST 2084 perceptual quantizer. The default direction converts the PQ
+///   signal to absolute luminance scaled so that `peak_nits` maps to `max_value` (brighter
+///   signals clip); `decoding: true` converts luminance from `0` to `peak_nits` to the PQ signal.
+/// - `hlg`: ARIB STD-B67 / BT.2100 Hybrid Log-Gamma. The default direction applies the inverse
+///   OETF followed by the OOTF (`scene ^ system_gamma`), converting the HLG signal to display
+///   light; `decoding: true` applies the inverse OOTF followed by the OETF.
+/// - `cie_lightness`: CIE 1976 `L*`, with its linear segment below `L* = 8`. The default
+///   direction converts perceived lightness (`L* / 100`) to relative luminance, which suits
+///   LED dimming; `decoding: true` converts relative luminance to lightness.
+/// - `dali`: The IEC 62386 (DALI) logarithmic dimming curve, `10 ^ (3 (n - 1) / 253 - 3)` for
+///   arc power levels `n` from `1` (0.1%) to `254` (100%), with level `0` switching off. The
+///   input is scaled to levels `0..=254`, so `size: 255` has one entry per level. The default
+///   direction converts the arc power level to light output; `decoding: true` converts light
+///   output to the arc power level, where only zero light gives level `0` and any other light
+///   gives at least level `1`.
+/// - `linear`, `square_law`, `inverse_square_law`, `s_curve`: The standard dimmer curves of
+///   stage lighting consoles, `x`, `x²`, `√x` and the smoothstep `3x² - 2x³`. The default
+///   direction converts the fader (DMX) level to output level; `decoding: true` converts an
+///   output level back to the fader level.
+/// - `cineon`, `slog3`, `logc3`, `vlog`, `clog`: Camera log encodings (Kodak Cineon, Sony
+///   S-Log3, ARRI Log C v3 at EI 800, Panasonic V-Log and Canon Log) with their published
+///   constants. The default direction linearizes the log code value; scene light is divided
+///   by the value at code `1.0` so the whole code range fits the table, and codes below black
+///   clamp to `0`. `decoding: true` encodes linear light on the same scale to the log code value.
+/// - `acescc`, `acescct`: The ACES log encodings, including the handling of values at and
+///   below zero in `acescc` and the linear toe of `acescct`. Values stay in ACES units, where
+///   `1.0` maps to `max_value`: the default direction converts the code value to ACES linear
+///   (up to `222.86` at code `1.0`, and negative in the `acescct` toe), `decoding: true`
+///   converts ACES linear to the code value (negative for `acescc` near black). The macro
+///   reports an error if `entry_type` cannot hold the scaled output.
+/// - `gsdf`: The DICOM PS3.14 Grayscale Standard Display Function. Driving levels are spaced
+///   evenly in just-noticeable differences between `min_luminance` and `max_luminance`. The
+///   default direction converts the driving level to luminance, where `0` is `min_luminance`
+///   and `max_value` is `max_luminance`; `decoding: true` converts luminance on the same scale
+///   to the calibrated driving level.
+/// - `reinhard`, `hable`, `aces_fitted`: Tone-mapping operators (extended Reinhard, Hable's
+///   Uncharted 2 filmic curve and Hill's ACES RRT+ODT fit) from linear light in `0` to `white`
+///   to display-linear light. Reinhard and Hable map `white` to `max_value`; the ACES fit
+///   clamps to `0.0..=1.0`. With `gamma` the result is passed through the power law, so
+///   `gamma: 2.2, decoding: true` produces display-encoded values; without `gamma` these
+///   curves cannot be decoded.
+/// - `sigmoid`: A logistic contrast curve, `1 / (1 + e ^ (-steepness * (input - midpoint)))`,
+///   rescaled so that `0` maps to `0` and the last entry to `max_value`. Larger `steepness`
+///   values give more contrast around `midpoint`. Like the tone-mapping curves, an optional
+///   `gamma` stage is applied to the result, `decoding` selects its direction, and without
+///   `gamma` the curve cannot be decoded.
+/// - `ease_in_*`, `ease_out_*`, `ease_in_out_*` followed by `quad`, `cubic`, `sine`, `expo`,
+///   `back`, `elastic` or `bounce` (e.g. `ease_in_out_cubic`): Animation easing functions from
+///   progress to eased value, with the formulas of Robert Penner's easings. The ease-out form
+///   is `1 - ease_in(1 - t)`, and the ease-in-out form runs the ease-in over the first half and
+///   the ease-out over the second; as in Penner's ease-in-out equations, `back` then pulls back
+///   by `1.525` times as much and `elastic` oscillates more slowly. `back` and `elastic`
+///   overshoot, so their tables go below `0` or above `max_value` and may need a signed or
+///   larger `entry_type`. Easing curves cannot be decoded.
+/// - `bipolar`: A stick or joystick response curve centered on the middle index, with signed
+///   output (so `entry_type` must be signed). The first index is full negative deflection, the
+///   last is full positive deflection. Positions within `dead_zone` of the center give `0`,
+///   and the rest of each half is rescaled to `0.0..=1.0` and passed through the power law with
+///   `expo` as the gamma, so `decoding: true` uses `1/expo`. The first and last entries are
+///   `-max_value` and `max_value`.
+/// - `dot_gain`: Printing dot-gain compensation using the Yule-Nielsen model with a fully
+///   absorbing ink, where a dot of area `a` prints as `1 - (1 - a) ^ n` and `n` is solved so
+///   that a 50% dot prints as `0.5 + dot_gain`. The default direction converts the tone to
+///   print to the compensated dot area to image; `decoding: true` converts the imaged dot area
+///   to the printed tone.
+/// - `db_taper`: An audio volume taper in equal decibel steps. The default direction converts
+///   the knob position to a gain multiplier, `10 ^ ((dB - max_db) / 20)` with
+///   `dB = min_db + input * (max_db - min_db)`, so `max_value` is the gain at `max_db`; the
+///   first entry mutes instead of stopping at `min_db`. `decoding: true` converts a gain back
+///   to the knob position, with gains below `min_db` at position `0`.
+/// - `measured`: A curve through measured calibration points, e.g. colorimeter readings of an
+///   LED strip at several PWM levels. The points are normalized so that the first maps to `0`
+///   and the last to `max_value` on both axes, so raw units such as duty cycles and cd/m² can
+///   be used directly, and inputs outside the points take the nearest end value. `monotone`
+///   interpolation uses Fritsch–Carlson cubic splines, which are smooth and never decrease or
+///   overshoot the points; `linear` draws straight lines between them. The default direction
+///   converts the input to the measured output; `decoding: true` finds the lowest input that
+///   produces a given output. A CSV file holds one `input,output` pair per line, with an
+///   optional header row of column names first, and `#` comments and blank lines are skipped;
+///   any other line that is not two numbers is an error. The table is rebuilt when the file
+///   changes.
+/// - `icc`: The tone response curve of one channel of an ICC profile, read from its `curv`
+///   (a gamma or evenly spaced samples, interpolated linearly) or `para` (parametric function
+///   types 0 to 4) tag. The default direction converts the device value to linear light, as
+///   the profile defines it; `decoding: true` converts linear light to the lowest device value
+///   that produces it, which requires a rising curve. Without `channel`, an RGB profile
+///   generates `<NAME>_RED`, `<NAME>_GREEN` and `<NAME>_BLUE` instead of `<NAME>`, one table
+///   per channel, which a pipeline stage cannot do. The table is rebuilt when the profile
+///   changes.
+/// - `cube`: One column of a 1D `.cube` LUT from Adobe or Resolve grading tools, interpolated
+///   linearly between its rows so that any `size` can be generated. The table index spans
+///   inputs `0.0..=1.0`, which are looked up in the LUT's `DOMAIN_MIN`/`DOMAIN_MAX` or
+///   `LUT_1D_INPUT_RANGE` (`0.0..=1.0` by default; a domain reaching outside `0.0..=1.0` is
+///   rejected), and outputs are used as they are, so `1.0` maps to `max_value`. The default
+///   direction applies the LUT; `decoding: true` finds the lowest input that produces each
+///   output, which requires a rising LUT. 3D LUTs are rejected. The table is rebuilt when the
+///   file changes.
+/// - `mu_law`, `a_law`: ITU-T G.711 segmented companding for audio, coding 14-bit (μ-law)
+///   or 13-bit (A-law) samples as 8-bit codes. The default direction expands: the index is a
+///   code, `size` must be 256, and the output is the signed sample at the middle of the code's
+///   step (so `entry_type` must be signed), scaled so that full scale maps to
+///   `max_value + 1`; `max_value: 32767` gives 16-bit samples. `decoding: true` compresses:
+///   the index is a two's complement sample of `log2(size)` bits, shifted to the law's sample
+///   size, `size` must be a power of two, and the output is the code, so `max_value` must be
+///   `255`. Codes are in G.711 wire format unless `wire_format: false` is given.
+///
+/// # Expressions
+/// The `expr` parameter takes a Rust expression in `x`, which runs from `0.0` at the first
+/// entry to `1.0` at the last. Its value is scaled by `max_value` like any other curve. It may use:
+/// - Number literals, `x`, and the constants `PI`, `E` and `TAU`.
+/// - `+`, `-`, `*`, `/`, `%`, unary `-` and parentheses.
+/// - The functions `powf`, `powi`, `sqrt`, `cbrt`, `exp`, `ln`, `log`, `log10`, `log2`, `sin`,
+///   `cos`, `tan`, `asin`, `acos`, `atan`, `tanh`, `abs`, `floor`, `ceil`, `round`, `min`,
+///   `max` and `clamp`, called either as methods (`x.powf(2.2)`) or as functions
+///   (`powf(x, 2.2)`).
+/// - `if` / `else if` / `else` with comparisons combined by `&&`, `||` and `!`.
+///
+/// Expressions cannot be decoded. Unsupported syntax, and operations that produce NaN or
+/// infinity at any table entry, are reported at their location in the expression.
+///
+/// # Inversion
+/// With `invert: true` the table index covers the curve's output from its value at the first
+/// entry to its value at the last entry, and each entry holds the normalized input at which
+/// the curve reaches that output, scaled by `max_value`. The input is found by bisection to
+/// within `1e-12`, far below one step of any table, so the result matches a closed-form
+/// inverse after rounding. The curve is sampled at 4097 points first and must be finite and
+/// monotonic (rising or falling, but not constant); companding curves cannot be inverted.
+///
+/// # Fitting
+/// With `fit` the `points` are measured samples, and the table is the model that fits them
+/// best by least squares, in either direction like `gamma`. Sample inputs are
+/// divided by the last input and outputs by the last output, so the last sample is full
+/// scale; inputs must not be negative and must strictly increase. Two models are available:
+/// - `gamma`: `output = input ^ gamma`.
+/// - `gamma_with_black`: `output = black + (1 - black) * input ^ gamma`, for displays and LEDs
+///   that still emit light at zero input. The table includes the offset: encoding starts at
+///   `black`, and decoding maps outputs up to `black` to `0`.
+///
+/// Next to the table the macro emits `f64` constants named after it: `{name}_GAMMA` with the
+/// fitted gamma, `{name}_BLACK_OFFSET` with the normalized black offset (`gamma_with_black`
+/// only), and `{name}_RMS_ERROR` with the root mean square of the residuals in normalized
+/// output units.
+///
+/// # Pipelines
+/// `stages` takes a list of stages in braces, applied in order to the normalized input. Each
+/// stage's output feeds the next in `f64`, and only the final result is scaled by `max_value`
+/// and rounded, so no precision is lost between stages. A stage is either:
+/// - A curve, written with the same parameters as a whole table (`gamma`, `curve`, `expr`, the
+///   curve's own parameters, `decoding` and `invert`), e.g. `{ curve: srgb }` or
+///   `{ gamma: 2.5, decoding: true }`. Companding curves cannot be used in a pipeline.
+/// - A gain, `{ gain: 0.8, offset: 0.05 }`, which computes `input * gain + offset` (`offset`
+///   defaults to `0.0`).
+///
+/// Values between stages are not clamped. `decoding` applies to each stage rather than to the
+/// whole pipeline, but `invert: true` inverts the whole pipeline like any other curve.
+///
 /// # Output
 /// Generates a `const` array named as specified by `name`, with type `[entry_type; size]`.
 ///
 /// # Errors
 /// - Fails to compile if required parameters are missing or have invalid types.
+/// - Fails if `curve` names an unknown curve, or a parameter is given that the curve does not use.
+/// - Fails if a `piecewise` curve is not continuous at its knee.
+/// - Fails if `black_level` is negative or not below `white_level`.
+/// - Fails if `peak_nits` is not in `0.0 < peak_nits <= 10000.0`.
+/// - Fails if `system_gamma` is not positive.
+/// - Fails if `min_luminance` is not below `max_luminance`, or either is outside the GSDF
+///   range of 0.05 to 3993 cd/m².
 /// - Fails if `gamma` is not positive.
-/// - Fails if `size` is less than 3.
+/// - Fails if `min_db` is not less than `max_db`.
+/// - Fails if `dot_gain` is not greater than `-0.5` and less than `0.5`.
+/// - Fails if `midpoint` is outside `0.0..=1.0`, `steepness` is not positive, or `decoding` is
+///   set for `sigmoid` without `gamma`.
+/// - Fails if `white` is not positive, or `decoding` is set for a tone-mapping curve without `gamma`.
+/// - Fails if `decoding` is set for an easing or expression curve.
+/// - Fails if `invert` is set for a curve that is not monotonic, or for a companding curve.
+/// - Fails if a pipeline stage fails any of these checks (the message names the stage), if
+///   `decoding` is set for the whole pipeline, or if a stage evaluates to NaN or infinity.
+/// - Fails if `expr` uses unsupported syntax, or evaluates to NaN or infinity at any entry.
+/// - Fails if `fit` names an unknown model, or the samples are too few (2, or 3 for
+///   `gamma_with_black`), have negative or non-increasing inputs, or end with an output that is
+///   not positive, or if the best fit is at either end of the gamma range searched (reported
+///   at the data).
+/// - Fails if the ICC profile cannot be read, is malformed, or lacks the tag for `channel`
+///   (reported at the path), if `channel` is missing for an RGB profile in a pipeline stage, or
+///   if `decoding` is set for a curve that does not rise monotonically.
+/// - Fails if the `.cube` file cannot be read or is not a 1D LUT with `LUT_1D_SIZE` matching
+///   rows (reported at the path with the line number), if the selected column's domain reaches
+///   outside `0.0..=1.0`, if `channel` is missing when the columns differ or is `gray`, or if
+///   `decoding` is set for a LUT that does not rise monotonically.
+/// - Fails if `points` has fewer than 2 points, inputs that do not strictly increase, or outputs
+///   that decrease or are all the same (reported at the points), or if the CSV file cannot be
+///   read or has a line that is not two numbers.
+/// - Fails if `expo` is not positive, or `dead_zone` is outside `0.0..1.0`.
+/// - Fails if `size` is less than 3. Companding curves also fail unless `size` is 256 when
+///   expanding, or a power of two and `max_value` is 255 when compressing.
 /// - Fails if `max_value` exceeds the maximum for the chosen `entry_type`.
+/// - Fails if the curve's output, scaled by `max_value`, does not fit in `entry_type`
+///   (negative values need a signed type).
 ///
 /// # Examples
 /// Basic gamma encoding table:
@@ -90,6 +367,391 @@ use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
 /// }
 /// ```
 ///
+/// Exact sRGB linearization table:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: SRGB_TO_LINEAR,
+///     entry_type: u16,
+///     curve: srgb,
+///     size: 256,
+///     max_value: 65535
+/// }
+/// ```
+///
+/// Rec.709-style curve from explicit piecewise parameters:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: REC709_STYLE,
+///     entry_type: u8,
+///     knee: 0.081242858,
+///     slope: 4.5,
+///     offset: 0.099296827,
+///     power: 2.222222222,
+///     size: 256
+/// }
+/// ```
+///
+/// BT.1886 display table for a screen with a 0.1 cd/m² black level:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: BT1886_EOTF,
+///     entry_type: u16,
+///     curve: bt1886,
+///     black_level: 0.1,
+///     white_level: 100.0,
+///     size: 1024,
+///     max_value: 4095
+/// }
+/// ```
+///
+/// PQ signal to panel drive table for a 1000 cd/m² HDR panel:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: PQ_TO_PANEL,
+///     entry_type: u16,
+///     curve: pq,
+///     peak_nits: 1000.0,
+///     size: 1024,
+///     max_value: 4095
+/// }
+/// ```
+///
+/// Perceptually uniform LED dimming table:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: LED_LIGHTNESS,
+///     entry_type: u16,
+///     curve: cie_lightness,
+///     size: 256,
+///     max_value: 4095
+/// }
+/// ```
+///
+/// DALI arc power level to 16-bit PWM duty cycle:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: DALI_TO_PWM,
+///     entry_type: u16,
+///     curve: dali,
+///     size: 255,
+///     max_value: 65535
+/// }
+///
+/// assert_eq!(DALI_TO_PWM[0], 0);
+/// assert_eq!(DALI_TO_PWM[1], 66); // 0.1%
+/// assert_eq!(DALI_TO_PWM[254], 65535);
+/// ```
+///
+/// Square law dimmer curve for a 16-bit DMX channel pair, indexed by the coarse channel:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: DMX_SQUARE_LAW,
+///     entry_type: u16,
+///     curve: square_law,
+///     size: 256,
+///     max_value: 65535
+/// }
+/// ```
+///
+/// S-Log3 linearization table for 10-bit footage:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: SLOG3_TO_LINEAR,
+///     entry_type: u16,
+///     curve: slog3,
+///     size: 1024,
+///     max_value: 65535
+/// }
+/// ```
+///
+/// ACES log encoding table; negative code values need a signed `entry_type`:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: LINEAR_TO_ACESCC,
+///     entry_type: i16,
+///     curve: acescc,
+///     size: 1024,
+///     max_value: 16384,
+///     decoding: true
+/// }
+/// ```
+///
+/// DICOM GSDF calibration table for a 0.5 to 350 cd/m² display:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: GSDF_CALIBRATION,
+///     entry_type: u16,
+///     curve: gsdf,
+///     min_luminance: 0.5,
+///     max_luminance: 350.0,
+///     size: 1024,
+///     decoding: true
+/// }
+/// ```
+///
+/// Tone-mapping table from linear HDR (up to 4x white) to 8-bit display-encoded values:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: HDR_TO_SDR,
+///     entry_type: u8,
+///     curve: reinhard,
+///     white: 4.0,
+///     gamma: 2.2,
+///     size: 1024,
+///     max_value: 255,
+///     decoding: true
+/// }
+/// ```
+///
+/// Contrast boost for 8-bit display-encoded values:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: CONTRAST,
+///     entry_type: u8,
+///     curve: sigmoid,
+///     midpoint: 0.5,
+///     steepness: 6.0,
+///     size: 256,
+///     max_value: 255
+/// }
+/// ```
+///
+/// Easing table for a 64-step animation, with the elastic overshoot in a signed type:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: EASE_OUT_ELASTIC,
+///     entry_type: i16,
+///     curve: ease_out_elastic,
+///     size: 64,
+///     max_value: 1000
+/// }
+///
+/// assert_eq!(EASE_OUT_ELASTIC[0], 0);
+/// assert!(EASE_OUT_ELASTIC.iter().any(|&v| v > 1000));
+/// assert_eq!(EASE_OUT_ELASTIC[63], 1000);
+/// ```
+///
+/// RC stick expo curve for a 10-bit ADC with a 5% dead zone around the center:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: STICK_EXPO,
+///     entry_type: i16,
+///     curve: bipolar,
+///     expo: 2.0,
+///     dead_zone: 0.05,
+///     size: 1024,
+///     max_value: 500
+/// }
+///
+/// assert_eq!(STICK_EXPO[0], -500);
+/// assert_eq!(STICK_EXPO[512], 0);
+/// assert_eq!(STICK_EXPO[1023], 500);
+/// ```
+///
+/// Dot-gain compensation for a press that prints a 50% dot as 68%:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: DOT_GAIN_COMPENSATION,
+///     entry_type: u8,
+///     curve: dot_gain,
+///     dot_gain: 0.18,
+///     size: 256,
+///     max_value: 255
+/// }
+///
+/// assert_eq!(DOT_GAIN_COMPENSATION[128], 88);
+/// ```
+///
+/// Custom curve from an expression, here sRGB written out by hand:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: CUSTOM_SRGB,
+///     entry_type: u8,
+///     expr: if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) },
+///     size: 256
+/// }
+/// ```
+///
+/// Inverse of a sigmoid contrast curve, which `decoding` cannot provide:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: CONTRAST,
+///     entry_type: u8,
+///     curve: sigmoid,
+///     steepness: 6.0,
+///     size: 256,
+///     max_value: 255
+/// }
+///
+/// gamma_table! {
+///     name: UNDO_CONTRAST,
+///     entry_type: u8,
+///     curve: sigmoid,
+///     steepness: 6.0,
+///     size: 256,
+///     max_value: 255,
+///     invert: true
+/// }
+///
+/// assert_eq!(UNDO_CONTRAST[usize::from(CONTRAST[100])], 100);
+/// ```
+///
+/// Pipeline from sRGB to an LED with gamma 2.5 at 80% brightness, rounded only once:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: SRGB_TO_LED,
+///     entry_type: u8,
+///     stages: [
+///         { curve: srgb },
+///         { gain: 0.8 },
+///         { gamma: 2.5, decoding: true },
+///     ],
+///     size: 256,
+///     max_value: 255
+/// }
+///
+/// gamma_table! {
+///     name: GAMMA_22_TO_25,
+///     entry_type: u16,
+///     from_gamma: 2.2,
+///     to_gamma: 2.5,
+///     size: 1024,
+///     max_value: 4095
+/// }
+/// ```
+///
+/// LED strip linearized from colorimeter readings at a few PWM levels, so that the table index
+/// is the desired brightness and the entry is the PWM value to output:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: LED_CALIBRATION,
+///     entry_type: u8,
+///     points: [(0.0, 0.2), (32.0, 3.1), (64.0, 14.8), (128.0, 71.5), (192.0, 160.0), (255.0, 262.0)],
+///     interpolation: monotone,
+///     size: 256,
+///     decoding: true
+/// }
+/// ```
+///
+/// Display gamma fitted to luminance readings in cd/m², with the fit reported as constants:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: PANEL_GAMMA,
+///     entry_type: u8,
+///     points: [(0.0, 0.3), (64.0, 12.1), (128.0, 53.0), (192.0, 128.5), (255.0, 240.0)],
+///     fit: gamma_with_black,
+///     size: 256,
+///     decoding: true
+/// }
+///
+/// assert!((PANEL_GAMMA_GAMMA - 2.2).abs() < 0.1);
+/// assert!(PANEL_GAMMA_RMS_ERROR < 0.01);
+/// ```
+///
+/// Per-channel correction tables from a monitor's ICC profile, generated as `MONITOR_RED`,
+/// `MONITOR_GREEN` and `MONITOR_BLUE`:
+/// ```ignore
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: MONITOR,
+///     entry_type: u16,
+///     icc_profile: "profiles/monitor.icc",
+///     size: 1024,
+///     max_value: 4095,
+///     decoding: true
+/// }
+/// ```
+///
+/// A colorist's `.cube` LUT resampled for firmware, with the result exported as
+/// `FILM_LOOK_CUBE` to check in a grading tool:
+/// ```ignore
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: FILM_LOOK,
+///     entry_type: u16,
+///     cube_lut: "luts/film_look.cube",
+///     channel: green,
+///     size: 256,
+///     max_value: 4095,
+///     export_cube: true
+/// }
+/// ```
+///
+/// G.711 μ-law compression of 14-bit samples to 8-bit codes:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: MU_LAW_COMPRESS,
+///     entry_type: u8,
+///     curve: mu_law,
+///     size: 16384,
+///     max_value: 255,
+///     decoding: true
+/// }
+///
+/// assert_eq!(MU_LAW_COMPRESS[0], 0xFF);
+/// let code = MU_LAW_COMPRESS[(-100_i16 as u16 & 0x3FFF) as usize];
+/// assert_eq!(code, 0x5F);
+/// ```
+///
+/// Volume knob to gain table with a -48 dB floor:
+/// ```
+/// use gamma_table_macros::gamma_table;
+///
+/// gamma_table! {
+///     name: VOLUME_TO_GAIN,
+///     entry_type: u16,
+///     curve: db_taper,
+///     min_db: -48.0,
+///     max_db: 0.0,
+///     size: 128,
+///     max_value: 32768
+/// }
+/// ```
+///
 /// # Usage
 /// The generated table can be used as a `const` array in your code:
 /// ```ignore
@@ -100,7 +762,7 @@ use syn::{parse_macro_input, Error, LitBool, LitFloat, LitInt};
 pub fn gamma_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as GammaTableInput);
 
-    match generate_gamma_table(&input) {
+    match generate_gamma_tables(&input) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.to_compile_error().into(),
     }
@@ -109,20 +771,23 @@ pub fn gamma_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 struct GammaTableInput {
     name: syn::Ident,
     entry_type: syn::Type,
-    gamma: f64,
+    curve: Curve,
     size: usize,
     max_value: Option<u64>,
     decoding: Option<bool>,
+    export_cube: bool,
 }
 
 impl syn::parse::Parse for GammaTableInput {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut name = None;
         let mut entry_type = None;
-        let mut gamma = None;
+        let mut curve = CurveParams::default();
         let mut size = None;
         let mut max_value = None;
         let mut decoding = None;
+        let mut invert = false;
+        let mut export_cube = false;
 
         while !input.is_empty() {
             let ident: syn::Ident = input.parse()?;
@@ -137,9 +802,11 @@ impl syn::parse::Parse for GammaTableInput {
                     let value: syn::Type = input.parse()?;
                     entry_type = Some(value);
                 }
-                "gamma" => {
-                    let value: LitFloat = input.parse()?;
-                    gamma = Some(value.base10_parse()?);
+                "stages" => {
+                    let content;
+                    syn::bracketed!(content in input);
+                    let stages = content.parse_terminated(parse_stage, syn::Token![,])?;
+                    curve.set_stages(ident.span(), stages.into_iter().collect());
                 }
                 "size" => {
                     let value: LitInt = input.parse()?;
@@ -153,12 +820,19 @@ impl syn::parse::Parse for GammaTableInput {
                     let value: LitBool = input.parse()?;
                     decoding = Some(value.value);
                 }
-                _ => {
-                    return Err(Error::new(
-                        ident.span(),
-                        format!("Unknown parameter: {ident}"),
-                    ))
+                "invert" => {
+                    let value: LitBool = input.parse()?;
+                    invert = value.value;
+                }
+                "fit" => {
+                    let value: syn::Ident = input.parse()?;
+                    curve.set_fit(value);
+                }
+                "export_cube" => {
+                    let value: LitBool = input.parse()?;
+                    export_cube = value.value;
                 }
+                _ => parse_curve_parameter(&ident, input, &mut curve)?,
             }
 
             if input.peek(syn::Token![,]) {
@@ -172,25 +846,33 @@ impl syn::parse::Parse for GammaTableInput {
             entry_type: entry_type.ok_or_else(|| {
                 Error::new(input.span(), "Missing required parameter: entry_type")
             })?,
-            gamma: gamma
-                .ok_or_else(|| Error::new(input.span(), "Missing required parameter: gamma"))?,
+            curve: if invert {
+                Curve::Inverse(Box::new(curve.into_curve(input.span())?))
+            } else {
+                curve.into_curve(input.span())?
+            },
             size: size
                 .ok_or_else(|| Error::new(input.span(), "Missing required parameter: size"))?,
             max_value,
             decoding,
+            export_cube,
         })
     }
 }
 
-fn get_integer_type_max_value(entry_type: &syn::Type) -> Option<u64> {
+fn get_integer_type_range(entry_type: &syn::Type) -> Option<(i128, i128)> {
     // Extract the type name from syn::Type
     if let syn::Type::Path(type_path) = entry_type {
         if let Some(segment) = type_path.path.segments.last() {
             match segment.ident.to_string().as_str() {
-                "u8" => Some(u64::from(u8::MAX)),
-                "u16" => Some(u64::from(u16::MAX)),
-                "u32" => Some(u64::from(u32::MAX)),
-                "u64" => Some(u64::MAX),
+                "u8" => Some((0, i128::from(u8::MAX))),
+                "u16" => Some((0, i128::from(u16::MAX))),
+                "u32" => Some((0, i128::from(u32::MAX))),
+                "u64" => Some((0, i128::from(u64::MAX))),
+                "i8" => Some((i128::from(i8::MIN), i128::from(i8::MAX))),
+                "i16" => Some((i128::from(i16::MIN), i128::from(i16::MAX))),
+                "i32" => Some((i128::from(i32::MIN), i128::from(i32::MAX))),
+                "i64" => Some((i128::from(i64::MIN), i128::from(i64::MAX))),
                 _ => None, // Unknown or unsupported type
             }
         } else {
@@ -201,17 +883,157 @@ fn get_integer_type_max_value(entry_type: &syn::Type) -> Option<u64> {
     }
 }
 
+/// Parses the value of a parameter that selects or configures the curve.
+fn parse_curve_parameter(
+    ident: &syn::Ident,
+    input: syn::parse::ParseStream,
+    curve: &mut CurveParams,
+) -> syn::Result<()> {
+    match ident.to_string().as_str() {
+        "curve" => {
+            let value: syn::Ident = input.parse()?;
+            curve.set_kind(value);
+        }
+        "expr" => {
+            let value: syn::Expr = input.parse()?;
+            curve.set_expression(Expression::new(&value)?);
+        }
+        "points" => {
+            if input.peek(LitStr) {
+                let path: LitStr = input.parse()?;
+                curve.set_points(Points::from_csv(&path)?);
+            } else {
+                let content;
+                let bracket = syn::bracketed!(content in input);
+                let values = content.parse_terminated(parse_point, syn::Token![,])?;
+                curve.set_points(Points {
+                    span: bracket.span.join(),
+                    values: values.into_iter().collect(),
+                    source: None,
+                });
+            }
+        }
+        "interpolation" => {
+            let value: syn::Ident = input.parse()?;
+            curve.set_interpolation(value);
+        }
+        "icc_profile" => {
+            let path: LitStr = input.parse()?;
+            curve.set_icc_profile(IccProfile::load(&path)?);
+        }
+        "cube_lut" => {
+            let path: LitStr = input.parse()?;
+            curve.set_cube_lut(CubeLut::load(&path)?);
+        }
+        "channel" => {
+            let value: syn::Ident = input.parse()?;
+            curve.set_channel(value);
+        }
+        "wire_format" => {
+            let value: LitBool = input.parse()?;
+            curve.set_wire_format(value);
+        }
+        name if CurveParams::is_float_parameter(name) => {
+            let value = parse_float(input)?;
+            curve.set(ident.clone(), value);
+        }
+        _ => {
+            return Err(Error::new(
+                ident.span(),
+                format!("Unknown parameter: {ident}"),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Parses one `{ ... }` stage of a `stages` list.
+fn parse_stage(input: syn::parse::ParseStream) -> syn::Result<Stage> {
+    let content;
+    let brace = syn::braced!(content in input);
+    let mut curve = CurveParams::default();
+    let mut gain = None;
+    let mut decoding = None;
+    let mut invert = false;
+
+    while !content.is_empty() {
+        let ident: syn::Ident = content.parse()?;
+        content.parse::<syn::Token![:]>()?;
+
+        match ident.to_string().as_str() {
+            "gain" => gain = Some(parse_float(&content)?),
+            "decoding" => {
+                let value: LitBool = content.parse()?;
+                decoding = Some(value.value);
+            }
+            "invert" => {
+                let value: LitBool = content.parse()?;
+                invert = value.value;
+            }
+            _ => parse_curve_parameter(&ident, &content, &mut curve)?,
+        }
+
+        if content.peek(syn::Token![,]) {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+
+    curve.into_stage(gain, decoding, invert, brace.span.join())
+}
+
+/// Parses one `(input, output)` calibration point.
+fn parse_point(input: syn::parse::ParseStream) -> syn::Result<(f64, f64)> {
+    let content;
+    syn::parenthesized!(content in input);
+    let point_input = parse_float(&content)?;
+    content.parse::<syn::Token![,]>()?;
+    let output = parse_float(&content)?;
+    Ok((point_input, output))
+}
+
+/// Parses a float literal with an optional leading minus sign.
+fn parse_float(input: syn::parse::ParseStream) -> syn::Result<f64> {
+    let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+    let value: LitFloat = input.parse()?;
+    let value: f64 = value.base10_parse()?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Generates the table, or one table per channel, named `<NAME>_RED` and so on, for an RGB
+/// profile read without a `channel`.
+fn generate_gamma_tables(input: &GammaTableInput) -> syn::Result<TokenStream> {
+    let Some(channels) = input.curve.split_channels() else {
+        return generate_gamma_table(input);
+    };
+    channels
+        .into_iter()
+        .map(|(channel, curve)| {
+            generate_gamma_table(&GammaTableInput {
+                name: format_ident!("{}_{}", input.name, channel.suffix()),
+                entry_type: input.entry_type.clone(),
+                curve,
+                ..*input
+            })
+        })
+        .collect()
+}
+
 fn generate_gamma_table(input: &GammaTableInput) -> syn::Result<TokenStream> {
     let name = &input.name;
     let entry_type = &input.entry_type;
-    let gamma = input.gamma;
+    let curve = &input.curve;
     let size = input.size;
     let max_value = input.max_value.unwrap_or((size - 1) as u64);
     let decoding = input.decoding.unwrap_or(false);
 
     // Validate input parameters
-    if gamma <= 0.0 {
-        return Err(Error::new(name.span(), "Gamma value must be positive"));
+    if let Err(message) = curve.validate() {
+        return Err(Error::new(name.span(), message));
+    }
+    if decoding {
+        if let Err(message) = curve.validate_decoding() {
+            return Err(Error::new(name.span(), message));
+        }
     }
     if size < 3 {
         return Err(Error::new(
@@ -219,32 +1041,56 @@ fn generate_gamma_table(input: &GammaTableInput) -> syn::Result<TokenStream> {
             "Size must be at least 3 to create a meaningful gamma table. Smaller sizes only have min and max values.",
         ));
     }
+    if let Err(message) = curve.validate_size(size, max_value, decoding) {
+        return Err(Error::new(name.span(), message));
+    }
+    if let Err(message) = curve.validate_inverse(decoding) {
+        return Err(Error::new(name.span(), message));
+    }
+    curve.validate_values(size, name.span())?;
 
     // Validate that max_value fits in the target integer type
-    if let Some(type_max) = get_integer_type_max_value(entry_type) {
-        if max_value > type_max {
-            return Err(Error::new(
-                name.span(),
-                format!(
-                    "max_value ({}) exceeds the maximum value ({}) that can be stored in entry_type {}",
-                    max_value,
-                    type_max,
-                    quote!(#entry_type)
-                ),
-            ));
-        }
-    } else {
+    let Some((type_min, type_max)) = get_integer_type_range(entry_type) else {
+        return Err(Error::new(
+            name.span(),
+            format!(
+                "Unsupported entry_type: {}. Supported types are: u8, u16, u32, u64, i8, i16, i32, i64",
+                quote!(#entry_type)
+            ),
+        ));
+    };
+    if i128::from(max_value) > type_max {
         return Err(Error::new(
             name.span(),
             format!(
-                "Unsupported entry_type: {}. Supported types are: u8, u16, u32, u64",
+                "max_value ({}) exceeds the maximum value ({}) that can be stored in entry_type {}",
+                max_value,
+                type_max,
                 quote!(#entry_type)
             ),
         ));
     }
 
     // Generate the lookup table values
-    let values = generate_table_values(size, gamma, max_value, decoding);
+    let values = generate_table_values(size, curve, max_value, decoding);
+
+    // Validate that the curve's output domain fits in the target integer type
+    let lowest = values.iter().copied().min().unwrap_or(0);
+    let highest = values.iter().copied().max().unwrap_or(0);
+    if lowest < type_min || highest > type_max {
+        let hint = if lowest < type_min && type_min == 0 {
+            "Use a signed entry_type for negative values"
+        } else {
+            "Use a larger entry_type or a smaller max_value"
+        };
+        return Err(Error::new(
+            name.span(),
+            format!(
+                "Curve output ranges from {lowest} to {highest} with max_value {max_value}, which cannot be stored in entry_type {} ({type_min} to {type_max}). {hint}",
+                quote!(#entry_type)
+            ),
+        ));
+    }
 
     // Convert values to tokens with proper casting
     let value_tokens: Vec<TokenStream> = values
@@ -252,34 +1098,88 @@ fn generate_gamma_table(input: &GammaTableInput) -> syn::Result<TokenStream> {
         .map(|&v| quote! { #v as #entry_type })
         .collect();
 
+    // Report a fitted curve next to the table
+    let fit_constants = curve.fit().map(|fit| {
+        let gamma_name = format_ident!("{}_GAMMA", name);
+        let rms_error_name = format_ident!("{}_RMS_ERROR", name);
+        let (gamma, rms_error) = (fit.gamma, fit.rms_error);
+        let black_offset = fit.black_offset.map(|black_offset| {
+            let black_offset_name = format_ident!("{}_BLACK_OFFSET", name);
+            quote! {
+                #[allow(dead_code)]
+                const #black_offset_name: f64 = #black_offset;
+            }
+        });
+        quote! {
+            #[allow(dead_code)]
+            const #gamma_name: f64 = #gamma;
+            #black_offset
+            #[allow(dead_code)]
+            const #rms_error_name: f64 = #rms_error;
+        }
+    });
+
+    let cube_constant = input
+        .export_cube
+        .then(|| cube_constant(name, &values, max_value));
+
+    // Rebuild the table when a file it was read from changes
+    let source_files = curve
+        .source_files()
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned());
+
     Ok(quote! {
+        #(const _: &[u8] = include_bytes!(#source_files);)*
         const #name: [#entry_type; #size] = [#(#value_tokens),*];
+        #fit_constants
+        #cube_constant
     })
 }
 
-fn generate_table_values(size: usize, gamma: f64, max_value: u64, decoding: bool) -> Vec<u64> {
-    let mut values = Vec::with_capacity(size);
+/// Generates `<NAME>_CUBE`, the table as a `.cube` file for the user to write out.
+fn cube_constant(name: &syn::Ident, values: &[i128], max_value: u64) -> TokenStream {
+    let cube_name = format_ident!("{}_CUBE", name);
+    let text = format_cube(&name.to_string(), values, max_value);
+    quote! {
+        #[allow(dead_code)]
+        const #cube_name: &str = #text;
+    }
+}
 
-    // Choose gamma exponent based on mode
-    let gamma_exponent = if decoding {
-        1.0 / gamma // Gamma correction/decoding: input^(1/gamma)
-    } else {
-        gamma // Gamma encoding (default): input^gamma
-    };
+/// Evaluates `curve` for each table index and scales the result by `max_value`.
+///
+/// Curves with a sign bit are filled from whole G.711 codes instead. When expanding, the index
+/// is a code and the output is its signed sample, scaled so that full scale maps to
+/// `max_value + 1`. When decoding (compressing), the index is a two's complement sample and
+/// the output is its code.
+fn generate_table_values(size: usize, curve: &Curve, max_value: u64, decoding: bool) -> Vec<i128> {
+    if let Curve::Companding { law, wire_format } = curve {
+        return if decoding {
+            law.compression_table(size, *wire_format)
+        } else {
+            law.expansion_table(max_value, *wire_format)
+        };
+    }
+
+    let mut values = Vec::with_capacity(size);
+    let limit = i128::from(max_value);
 
-    // Direct gamma processing for each entry
+    // Direct curve evaluation for each entry
     for i in 0..size {
         #[allow(clippy::cast_precision_loss)]
         let normalized_input = i as f64 / (size - 1) as f64;
-        let processed = normalized_input.powf(gamma_exponent);
-        // we know the the sign is positive, and the result values will fit in a u64, and we are rounding
-        #[allow(
-            clippy::cast_precision_loss,
-            clippy::cast_possible_truncation,
-            clippy::cast_sign_loss
-        )]
-        let output_value = (processed * max_value as f64).round() as u64;
-        values.push(output_value.min(max_value));
+        let processed = curve.eval(normalized_input, decoding);
+        // the conversion saturates, and out of range values are reported by the caller
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        let output_value = (processed * max_value as f64).round() as i128;
+        // keep floating-point rounding from pushing the nominal range past max_value
+        let output_value = if processed.abs() <= 1.0 {
+            output_value.clamp(-limit, limit)
+        } else {
+            output_value
+        };
+        values.push(output_value);
     }
 
     values
@@ -292,7 +1192,7 @@ mod tests {
     #[test]
     fn test_gamma_encoding_default() {
         // Test gamma encoding (default behavior)
-        let values = generate_table_values(256, 2.2, 255, false);
+        let values = generate_table_values(256, &Curve::Power { gamma: 2.2 }, 255, false);
         assert_eq!(values.len(), 256);
         assert_eq!(values[0], 0);
         assert_eq!(values[255], 255);
@@ -306,7 +1206,7 @@ mod tests {
     #[test]
     fn test_gamma_decoding() {
         // Test gamma correction/decoding
-        let values = generate_table_values(256, 2.2, 255, true);
+        let values = generate_table_values(256, &Curve::Power { gamma: 2.2 }, 255, true);
         assert_eq!(values.len(), 256);
         assert_eq!(values[0], 0);
         assert_eq!(values[255], 255);
@@ -319,8 +1219,8 @@ mod tests {
 
     #[test]
     fn test_encoding_vs_decoding_difference() {
-        let encoding_values = generate_table_values(10, 2.2, 100, false);
-        let decoding_values = generate_table_values(10, 2.2, 100, true);
+        let encoding_values = generate_table_values(10, &Curve::Power { gamma: 2.2 }, 100, false);
+        let decoding_values = generate_table_values(10, &Curve::Power { gamma: 2.2 }, 100, true);
 
         // Encoding and decoding should produce different results for mid-values
         assert_ne!(encoding_values[5], decoding_values[5]);
@@ -333,7 +1233,7 @@ mod tests {
     #[test]
     fn test_default_max_value() {
         // Test that max_value defaults to size-1
-        let values = generate_table_values(10, 1.0, 9, false);
+        let values = generate_table_values(10, &Curve::Power { gamma: 1.0 }, 9, false);
         assert_eq!(values[0], 0);
         assert_eq!(values[9], 9); // size-1
     }
@@ -344,10 +1244,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 2,
             max_value: None,
             decoding: None,
+            export_cube: false,
         };
 
         let result = generate_gamma_table(&input);
@@ -361,10 +1262,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 3,
             max_value: None,
             decoding: None,
+            export_cube: false,
         };
 
         let result = generate_gamma_table(&input);
@@ -377,10 +1279,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: -1.0,
+            curve: Curve::Power { gamma: -1.0 },
             size: 10,
             max_value: None,
             decoding: None,
+            export_cube: false,
         };
 
         let result = generate_gamma_table(&input);
@@ -394,10 +1297,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: 0.0,
+            curve: Curve::Power { gamma: 0.0 },
             size: 10,
             max_value: None,
             decoding: None,
+            export_cube: false,
         };
 
         let result = generate_gamma_table(&input);
@@ -562,15 +1466,271 @@ mod tests {
     }
 
     #[test]
+    fn test_parsing_expression() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, expr: x.powf(2.0) * 0.5, size: 10
+        })
+        .unwrap();
+        assert!((input.curve.eval(1.0, false) - 0.5).abs() < 1e-12);
+
+        // Errors point inside the expression
+        let result = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, expr: x.powf(2.0).gamma(), size: 10
+        });
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Unknown function: gamma"));
+
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, expr: 1.0 / x, size: 10
+        })
+        .unwrap();
+        let result = generate_gamma_table(&input);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Expression evaluates to inf at x = 0"
+        );
+    }
+
+    #[test]
+    fn test_parsing_stages() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            stages: [
+                { curve: srgb },
+                { gain: 0.5, offset: -0.1 },
+                { gamma: 2.0, decoding: true, invert: true }
+            ]
+        })
+        .unwrap();
+        let Curve::Pipeline(stages) = &input.curve else {
+            panic!("expected a pipeline, got {:?}", input.curve);
+        };
+        assert_eq!(stages.len(), 3);
+        assert_eq!(
+            stages[1],
+            Stage::Affine {
+                gain: 0.5,
+                offset: -0.1
+            }
+        );
+
+        // Stages only accept curve parameters and gain stages
+        for stages in [
+            quote! { [{ size: 10 }] },
+            quote! { [{ curve: srgb, gain: 0.5 }] },
+            quote! { [{ gamma: 2.2 }, { curve: srgb }], gamma: 2.2 },
+        ] {
+            let result = syn::parse2::<GammaTableInput>(quote! {
+                name: TABLE, entry_type: u8, size: 10, stages: #stages
+            });
+            assert!(result.is_err(), "{stages}");
+        }
+
+        // Decoding is set per stage
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10, from_gamma: 2.2, to_gamma: 2.5, decoding: true
+        })
+        .unwrap();
+        assert!(generate_gamma_table(&input).is_err());
+    }
+
+    #[test]
+    fn test_fit_constants() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            points: [(0.0, 0.0), (0.5, 0.25), (1.0, 1.0)],
+            fit: gamma
+        })
+        .unwrap();
+        assert!((input.curve.fit().unwrap().gamma - 2.0).abs() < 1e-9);
+        let output = generate_gamma_table(&input).unwrap().to_string();
+        assert!(output.contains("const TABLE_GAMMA : f64"), "{output}");
+        assert!(output.contains("const TABLE_RMS_ERROR : f64"), "{output}");
+        assert!(!output.contains("TABLE_BLACK_OFFSET"), "{output}");
+
+        // fit is not a stage parameter
+        let result = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            stages: [{ points: [(0.0, 0.0), (1.0, 1.0)], fit: gamma }]
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parsing_icc_profile() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            icc_profile: "tests/data/display.icc", channel: red
+        })
+        .unwrap();
+        assert!((input.curve.eval(0.5, false) - 0.5_f64.powf(563.0 / 256.0)).abs() < 1e-12);
+        assert_eq!(input.curve.source_files().len(), 1);
+
+        // Without a channel, an RGB profile gives a table per channel
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            icc_profile: "tests/data/display.icc", invert: true
+        })
+        .unwrap();
+        let tables = generate_gamma_tables(&input).unwrap().to_string();
+        for name in ["TABLE_RED", "TABLE_GREEN", "TABLE_BLUE"] {
+            assert!(tables.contains(&format!("const {name} :")), "{tables}");
+        }
+        assert!(!tables.contains("const TABLE :"), "{tables}");
+        // ... but a pipeline stage still needs one
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            stages: [{ icc_profile: "tests/data/display.icc" }]
+        })
+        .unwrap();
+        assert!(generate_gamma_tables(&input).is_err());
+
+        for parameters in [
+            // An RGB profile has no gray curve
+            quote! { icc_profile: "tests/data/display.icc", channel: gray },
+            quote! { icc_profile: "tests/data/display.icc", channel: alpha },
+            quote! { icc_profile: "tests/data/led_calibration.csv" },
+            quote! { icc_profile: "tests/data/missing.icc" },
+            quote! { curve: srgb, channel: red },
+        ] {
+            let result = syn::parse2::<GammaTableInput>(quote! {
+                name: TABLE, entry_type: u8, size: 10, #parameters
+            });
+            assert!(result.is_err(), "{parameters}");
+        }
+    }
+
+    #[test]
+    fn test_parsing_cube_lut() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, size: 10,
+            cube_lut: "tests/data/warm_film.cube", channel: blue, decoding: true
+        })
+        .unwrap();
+        assert!((input.curve.eval(0.44, true) - 0.5).abs() < 1e-12);
+        assert!(generate_gamma_table(&input).is_ok());
+
+        for parameters in [
+            // The columns differ, so a channel is needed
+            quote! { cube_lut: "tests/data/warm_film.cube" },
+            quote! { cube_lut: "tests/data/warm_film.cube", channel: gray },
+            quote! { cube_lut: "tests/data/led_calibration.csv" },
+            quote! { gamma: 2.2, export_cube: "out.cube" },
+        ] {
+            let result = syn::parse2::<GammaTableInput>(quote! {
+                name: TABLE, entry_type: u8, size: 10, #parameters
+            });
+            assert!(result.is_err(), "{parameters}");
+        }
+    }
+
+    #[test]
+    fn test_parsing_negative_float() {
+        let input = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, curve: db_taper, min_db: -48.0, max_db: -6.0, size: 10
+        })
+        .unwrap();
+        assert_eq!(
+            input.curve,
+            Curve::DbTaper {
+                min_db: -48.0,
+                max_db: -6.0
+            }
+        );
+
+        // A sign alone is not a value
+        let result = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: u8, curve: db_taper, min_db: -, size: 10
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_output_range_validation() {
+        // ACEScc decodes code 1.0 to 222.86, far above max_value
+        let input = GammaTableInput {
+            name: syn::parse_str("TEST_TABLE").unwrap(),
+            entry_type: syn::parse_str("u16").unwrap(),
+            curve: syn::parse2::<GammaTableInput>(quote! {
+                name: T, entry_type: u8, curve: acescc, size: 3
+            })
+            .unwrap()
+            .curve,
+            size: 1024,
+            max_value: None,
+            decoding: None,
+            export_cube: false,
+        };
+        let result = generate_gamma_table(&input);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Use a larger entry_type or a smaller max_value"));
+
+        // Signed types hold negative output
+        let values = generate_table_values(
+            3,
+            &syn::parse2::<GammaTableInput>(quote! {
+                name: T, entry_type: i8, curve: acescct, size: 3
+            })
+            .unwrap()
+            .curve,
+            100,
+            false,
+        );
+        assert_eq!(values[0], -1);
+    }
+
+    #[test]
+    fn test_sign_bit_layout() {
+        let curve = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: i16, curve: a_law, wire_format: false, size: 256
+        })
+        .unwrap()
+        .curve;
+
+        // Expansion: sign-magnitude codes, the upper half mirrors the lower half
+        let values = generate_table_values(256, &curve, 4095, false);
+        assert_eq!(values[..2], [1, 3]);
+        assert_eq!(values[127], 4032);
+        assert_eq!(values[128..130], [-1, -3]);
+
+        // Compression: two's complement samples, shifted to 13 bits
+        let values = generate_table_values(16, &curve, 255, true);
+        assert_eq!(values[0], 0);
+        assert_eq!(values[7], 0x7C); // 3584, step 12 of the last segment
+        assert_eq!(values[8], 0xFF); // -4096, full scale
+        assert_eq!(values[15], 0xCF); // -512, coded as 511 at the top of segment 4
+
+        // Wire codes are the default
+        let wire = syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: i16, curve: a_law, size: 256
+        })
+        .unwrap()
+        .curve;
+        assert_eq!(generate_table_values(16, &wire, 255, true)[0], 0xD5);
+        assert!(syn::parse2::<GammaTableInput>(quote! {
+            name: TABLE, entry_type: i16, gamma: 2.2, wire_format: true, size: 256
+        })
+        .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)] // one input per entry type
     fn test_max_value_overflow_validation() {
         // Test u8 overflow
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
             max_value: Some(300), // Exceeds u8::MAX (255)
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_err());
@@ -583,10 +1743,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u16").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
             max_value: Some(70000), // Exceeds u16::MAX (65535)
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_err());
@@ -599,10 +1760,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u32").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
-            max_value: Some(5000000000), // Exceeds u32::MAX (4294967295)
+            max_value: Some(5_000_000_000), // Exceeds u32::MAX (4294967295)
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_err());
@@ -615,10 +1777,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u8").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
             max_value: Some(255), // Valid for u8
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_ok());
@@ -627,10 +1790,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u32").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
-            max_value: Some(1000000), // Valid for u32
+            max_value: Some(1_000_000), // Valid for u32
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_ok());
@@ -639,10 +1803,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("u64").unwrap(),
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
-            max_value: Some(1000000), // Valid for u64
+            max_value: Some(1_000_000), // Valid for u64
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_ok());
@@ -650,11 +1815,12 @@ mod tests {
         // Test unsupported entry type
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
-            entry_type: syn::parse_str("i32").unwrap(), // Unsupported type
-            gamma: 2.2,
+            entry_type: syn::parse_str("i128").unwrap(), // Unsupported type
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
             max_value: Some(100),
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_err());
@@ -667,10 +1833,11 @@ mod tests {
         let input = GammaTableInput {
             name: syn::parse_str("TEST_TABLE").unwrap(),
             entry_type: syn::parse_str("f32").unwrap(), // Unsupported type
-            gamma: 2.2,
+            curve: Curve::Power { gamma: 2.2 },
             size: 10,
             max_value: Some(100),
             decoding: None,
+            export_cube: false,
         };
         let result = generate_gamma_table(&input);
         assert!(result.is_err());
@@ -0,0 +1,12 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the sRGB curve has a fixed exponent
+gamma_table! {
+    name: SRGB_WITH_GAMMA_TABLE,
+    entry_type: u8,
+    curve: srgb,
+    gamma: 2.2,
+    size: 256
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the taper's floor is above its top
+gamma_table! {
+    name: VOLUME_TABLE,
+    entry_type: u8,
+    curve: db_taper,
+    min_db: 0.0,
+    max_db: -60.0,
+    size: 256
+}
+
+fn main() {}
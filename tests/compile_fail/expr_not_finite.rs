@@ -0,0 +1,11 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because ln(0) is negative infinity at the first entry
+gamma_table! {
+    name: EXPR_TABLE,
+    entry_type: u8,
+    expr: 1.0 + x.ln() / 10.0,
+    size: 256
+}
+
+fn main() {}
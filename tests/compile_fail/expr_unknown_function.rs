@@ -0,0 +1,11 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the expression calls a function that does not exist
+gamma_table! {
+    name: EXPR_TABLE,
+    entry_type: u8,
+    expr: x.powf(2.2).gamma(),
+    size: 256
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because a black offset needs a third sample
+gamma_table! {
+    name: FIT_TOO_FEW_SAMPLES,
+    entry_type: u8,
+    points: [(0.0, 0.1), (1.0, 100.0)],
+    fit: gamma_with_black,
+    size: 256
+}
+
+fn main() {}
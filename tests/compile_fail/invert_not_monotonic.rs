@@ -0,0 +1,12 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the bouncing ball reaches the same height several times
+gamma_table! {
+    name: BOUNCE_TABLE,
+    entry_type: u8,
+    curve: ease_out_bounce,
+    size: 256,
+    invert: true
+}
+
+fn main() {}
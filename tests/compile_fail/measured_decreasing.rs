@@ -0,0 +1,11 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the calibration outputs decrease
+gamma_table! {
+    name: MEASURED_DECREASING,
+    entry_type: u8,
+    points: [(0.0, 0.0), (0.5, 0.8), (1.0, 0.6)],
+    size: 256
+}
+
+fn main() {}
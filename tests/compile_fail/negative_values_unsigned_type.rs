@@ -0,0 +1,13 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because ACEScc encodes linear black as a
+// negative code value, which u16 cannot hold
+gamma_table! {
+    name: UNSIGNED_ACESCC_TABLE,
+    entry_type: u16,
+    curve: acescc,
+    size: 1024,
+    decoding: true
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the linear segment (0.1 / 2.0 = 0.05)
+// does not meet the power segment at the knee
+gamma_table! {
+    name: DISCONTINUOUS_TABLE,
+    entry_type: u8,
+    knee: 0.1,
+    slope: 2.0,
+    offset: 0.055,
+    power: 2.4,
+    size: 256
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because the gain stage pushes values below zero before a square root
+gamma_table! {
+    name: PIPELINE_STAGE_NAN,
+    entry_type: u8,
+    stages: [{ gain: 2.0, offset: -1.0 }, { gamma: 0.5 }],
+    size: 16
+}
+
+fn main() {}
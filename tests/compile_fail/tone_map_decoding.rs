@@ -0,0 +1,12 @@
+use gamma_table_macros::gamma_table;
+
+// This should fail to compile because tone-mapping curves have no inverse
+gamma_table! {
+    name: HABLE_TABLE,
+    entry_type: u8,
+    curve: hable,
+    size: 256,
+    decoding: true
+}
+
+fn main() {}
@@ -51,6 +51,688 @@ gamma_table! {
     size: 3
 }
 
+// Test exact sRGB transfer function
+gamma_table! {
+    name: TEST_SRGB_TO_LINEAR_TABLE,
+    entry_type: u16,
+    curve: srgb,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_LINEAR_TO_SRGB_TABLE,
+    entry_type: u8,
+    curve: srgb,
+    size: 256,
+    decoding: true
+}
+
+// Test generic piecewise curve (sRGB constants spelled out)
+gamma_table! {
+    name: TEST_PIECEWISE_SRGB_TABLE,
+    entry_type: u16,
+    knee: 0.04045,
+    slope: 12.92,
+    offset: 0.055,
+    power: 2.4,
+    size: 256,
+    max_value: 65535
+}
+
+// Test video transfer presets
+gamma_table! {
+    name: TEST_REC709_OETF_TABLE,
+    entry_type: u16,
+    curve: rec709,
+    size: 1024,
+    max_value: 1023,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_REC2020_12BIT_OETF_TABLE,
+    entry_type: u16,
+    curve: rec2020_12bit,
+    size: 4096,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_BT1886_TABLE,
+    entry_type: u16,
+    curve: bt1886,
+    black_level: 0.1,
+    white_level: 100.0,
+    size: 256,
+    max_value: 10000
+}
+
+// Test PQ (SMPTE ST 2084) with a 1000 cd/m² peak
+gamma_table! {
+    name: TEST_PQ_TO_NITS_TABLE,
+    entry_type: u16,
+    curve: pq,
+    peak_nits: 1000.0,
+    size: 1024,
+    max_value: 1000
+}
+
+gamma_table! {
+    name: TEST_NITS_TO_PQ_TABLE,
+    entry_type: u16,
+    curve: pq,
+    peak_nits: 1000.0,
+    size: 1001,
+    max_value: 1023,
+    decoding: true
+}
+
+// Test HLG (ARIB STD-B67) in both directions, with the nominal 1000 cd/m² OOTF
+gamma_table! {
+    name: TEST_HLG_OETF_TABLE,
+    entry_type: u16,
+    curve: hlg,
+    size: 1024,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_HLG_DISPLAY_TABLE,
+    entry_type: u16,
+    curve: hlg,
+    system_gamma: 1.2,
+    size: 1024,
+    max_value: 65535
+}
+
+// Test CIE L* lightness in both directions
+gamma_table! {
+    name: TEST_LIGHTNESS_TABLE,
+    entry_type: u16,
+    curve: cie_lightness,
+    size: 101,
+    max_value: 10000
+}
+
+gamma_table! {
+    name: TEST_LUMINANCE_TO_LIGHTNESS_TABLE,
+    entry_type: u8,
+    curve: cie_lightness,
+    size: 256,
+    max_value: 100,
+    decoding: true
+}
+
+// Test the DALI dimming curve, one entry per arc power level
+gamma_table! {
+    name: TEST_DALI_TABLE,
+    entry_type: u16,
+    curve: dali,
+    size: 255,
+    max_value: 10000
+}
+
+gamma_table! {
+    name: TEST_LIGHT_TO_DALI_TABLE,
+    entry_type: u8,
+    curve: dali,
+    size: 1001,
+    max_value: 254,
+    decoding: true
+}
+
+// Test DMX dimmer curves on 8-bit and 16-bit channels
+gamma_table! {
+    name: TEST_DMX_LINEAR_TABLE,
+    entry_type: u8,
+    curve: linear,
+    size: 256,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_DMX_SQUARE_LAW_TABLE,
+    entry_type: u16,
+    curve: square_law,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_DMX_INVERSE_SQUARE_LAW_TABLE,
+    entry_type: u8,
+    curve: inverse_square_law,
+    size: 256,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_DMX_S_CURVE_TABLE,
+    entry_type: u8,
+    curve: s_curve,
+    size: 256,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_DMX_S_CURVE_DECODING_TABLE,
+    entry_type: u8,
+    curve: s_curve,
+    size: 256,
+    max_value: 255,
+    decoding: true
+}
+
+// Test camera log linearization and encoding
+gamma_table! {
+    name: TEST_SLOG3_TO_LINEAR_TABLE,
+    entry_type: u16,
+    curve: slog3,
+    size: 1024,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_LINEAR_TO_LOGC3_TABLE,
+    entry_type: u16,
+    curve: logc3,
+    size: 1024,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_CINEON_TO_LINEAR_TABLE,
+    entry_type: u16,
+    curve: cineon,
+    size: 1024,
+    max_value: 65535
+}
+
+// Test ACES log encodings, which need signed entries
+gamma_table! {
+    name: TEST_LINEAR_TO_ACESCC_TABLE,
+    entry_type: i16,
+    curve: acescc,
+    size: 1024,
+    max_value: 10000,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_ACESCCT_TO_LINEAR_TABLE,
+    entry_type: i32,
+    curve: acescct,
+    size: 1024,
+    max_value: 1000
+}
+
+// Test DICOM GSDF calibration for a 0.5 to 350 cd/m² display
+gamma_table! {
+    name: TEST_GSDF_TABLE,
+    entry_type: u8,
+    curve: gsdf,
+    min_luminance: 0.5,
+    max_luminance: 350.0,
+    size: 256
+}
+
+gamma_table! {
+    name: TEST_LUMINANCE_TO_GSDF_TABLE,
+    entry_type: u16,
+    curve: gsdf,
+    min_luminance: 0.5,
+    max_luminance: 350.0,
+    size: 1024,
+    decoding: true
+}
+
+// Test tone mapping from linear HDR, with and without display encoding
+gamma_table! {
+    name: TEST_REINHARD_TABLE,
+    entry_type: u8,
+    curve: reinhard,
+    white: 4.0,
+    gamma: 2.2,
+    size: 256,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_HABLE_TABLE,
+    entry_type: u16,
+    curve: hable,
+    size: 1024,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_ACES_FITTED_TABLE,
+    entry_type: u8,
+    curve: aces_fitted,
+    white: 16.0,
+    gamma: 2.2,
+    size: 256,
+    decoding: true
+}
+
+// Test G.711 companding: expansion is indexed by code and gives 16-bit samples,
+// compression is indexed by a two's complement 14-bit (μ-law) or 13-bit (A-law) sample
+gamma_table! {
+    name: TEST_MU_LAW_EXPAND_TABLE,
+    entry_type: i16,
+    curve: mu_law,
+    size: 256,
+    max_value: 32767
+}
+
+gamma_table! {
+    name: TEST_A_LAW_EXPAND_TABLE,
+    entry_type: i16,
+    curve: a_law,
+    size: 256,
+    max_value: 32767
+}
+
+gamma_table! {
+    name: TEST_MU_LAW_COMPRESS_TABLE,
+    entry_type: u8,
+    curve: mu_law,
+    size: 16384,
+    max_value: 255,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_A_LAW_COMPRESS_TABLE,
+    entry_type: u8,
+    curve: a_law,
+    size: 8192,
+    max_value: 255,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_MU_LAW_SIGN_MAGNITUDE_TABLE,
+    entry_type: i16,
+    curve: mu_law,
+    wire_format: false,
+    size: 256,
+    max_value: 8191
+}
+
+// Test the sigmoid contrast curve, alone and followed by gamma encoding
+gamma_table! {
+    name: TEST_SIGMOID_TABLE,
+    entry_type: u8,
+    curve: sigmoid,
+    steepness: 10.0,
+    size: 256,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_SIGMOID_ENCODED_TABLE,
+    entry_type: u16,
+    curve: sigmoid,
+    midpoint: 0.18,
+    steepness: 8.0,
+    gamma: 2.2,
+    size: 101,
+    max_value: 1000,
+    decoding: true
+}
+
+// Test animation easing tables, including overshoot into a signed type
+gamma_table! {
+    name: TEST_EASE_IN_OUT_CUBIC_TABLE,
+    entry_type: u8,
+    curve: ease_in_out_cubic,
+    size: 65,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_EASE_OUT_BOUNCE_TABLE,
+    entry_type: u16,
+    curve: ease_out_bounce,
+    size: 101,
+    max_value: 10000
+}
+
+gamma_table! {
+    name: TEST_EASE_IN_BACK_TABLE,
+    entry_type: i16,
+    curve: ease_in_back,
+    size: 11,
+    max_value: 1000
+}
+
+// Test bipolar stick curves, with an exact center and with a dead zone
+gamma_table! {
+    name: TEST_BIPOLAR_TABLE,
+    entry_type: i8,
+    curve: bipolar,
+    expo: 3.0,
+    size: 255,
+    max_value: 127
+}
+
+gamma_table! {
+    name: TEST_BIPOLAR_DEAD_ZONE_TABLE,
+    entry_type: i16,
+    curve: bipolar,
+    expo: 2.0,
+    dead_zone: 0.1,
+    size: 1001,
+    max_value: 1000
+}
+
+// Test dot-gain compensation and the matching printed tone prediction
+gamma_table! {
+    name: TEST_DOT_GAIN_TABLE,
+    entry_type: u8,
+    curve: dot_gain,
+    dot_gain: 0.18,
+    size: 101,
+    max_value: 100
+}
+
+gamma_table! {
+    name: TEST_PRINTED_TONE_TABLE,
+    entry_type: u8,
+    curve: dot_gain,
+    dot_gain: 0.18,
+    size: 101,
+    max_value: 100,
+    decoding: true
+}
+
+// Test expression curves against the built-in curves they reproduce
+gamma_table! {
+    name: TEST_EXPR_GAMMA_TABLE,
+    entry_type: u8,
+    expr: x.powf(2.2),
+    size: 256
+}
+
+gamma_table! {
+    name: TEST_EXPR_SRGB_TABLE,
+    entry_type: u16,
+    expr: if x <= 0.04045 { x / 12.92 } else { powf((x + 0.055) / 1.055, 2.4) },
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_EXPR_WAVE_TABLE,
+    entry_type: i8,
+    expr: (x * TAU).sin(),
+    size: 9,
+    max_value: 100
+}
+
+// Test numerical inverses against closed-form decoding and of curves without one
+gamma_table! {
+    name: TEST_INVERTED_GAMMA_TABLE,
+    entry_type: u8,
+    gamma: 2.2,
+    size: 256,
+    invert: true
+}
+
+gamma_table! {
+    name: TEST_INVERTED_SRGB_TABLE,
+    entry_type: u8,
+    curve: srgb,
+    size: 256,
+    invert: true
+}
+
+gamma_table! {
+    name: TEST_INVERTED_SIGMOID_TABLE,
+    entry_type: u8,
+    curve: sigmoid,
+    steepness: 10.0,
+    size: 256,
+    max_value: 255,
+    invert: true
+}
+
+gamma_table! {
+    name: TEST_INVERTED_EXPR_TABLE,
+    entry_type: u16,
+    expr: 1.0 - x.sqrt(),
+    size: 101,
+    max_value: 10000,
+    invert: true
+}
+
+// Test pipelines against single-stage tables and closed forms
+gamma_table! {
+    name: TEST_PIPELINE_SRGB_TABLE,
+    entry_type: u16,
+    stages: [{ curve: srgb }],
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_PIPELINE_LED_TABLE,
+    entry_type: u8,
+    stages: [
+        { curve: srgb },
+        { gain: 0.8 },
+        { gamma: 2.5, decoding: true },
+    ],
+    size: 256,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_TRANSCODE_TABLE,
+    entry_type: u16,
+    from_gamma: 2.2,
+    to_gamma: 2.5,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_TRANSCODE_POWER_TABLE,
+    entry_type: u16,
+    gamma: 0.88,
+    size: 256,
+    max_value: 65535
+}
+
+// Test tables interpolated through calibration points
+gamma_table! {
+    name: TEST_MEASURED_CSV_TABLE,
+    entry_type: u8,
+    points: "tests/data/led_calibration.csv",
+    size: 256
+}
+
+gamma_table! {
+    name: TEST_MEASURED_INLINE_TABLE,
+    entry_type: u8,
+    points: [
+        (0.0, 0.5),
+        (32.0, 4.0),
+        (64.0, 20.0),
+        (128.0, 90.0),
+        (192.0, 200.0),
+        (224.0, 201.0),
+        (255.0, 201.0),
+    ],
+    size: 256
+}
+
+gamma_table! {
+    name: TEST_MEASURED_LINEAR_TABLE,
+    entry_type: u8,
+    points: "tests/data/led_calibration.csv",
+    interpolation: linear,
+    size: 256
+}
+
+gamma_table! {
+    name: TEST_MEASURED_DECODING_TABLE,
+    entry_type: u16,
+    points: "tests/data/led_calibration.csv",
+    size: 256,
+    max_value: 1023,
+    decoding: true
+}
+
+// Test power laws fitted to measured samples
+gamma_table! {
+    name: TEST_FIT_TABLE,
+    entry_type: u16,
+    points: "tests/data/panel_luminance.csv",
+    fit: gamma,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_FIT_POWER_TABLE,
+    entry_type: u16,
+    gamma: 2.4,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_FIT_BLACK_TABLE,
+    entry_type: u8,
+    points: [
+        (0.0, 0.05),
+        (0.25, 0.094998),
+        (0.5, 0.256756),
+        (0.75, 0.554497),
+        (1.0, 1.0),
+    ],
+    fit: gamma_with_black,
+    size: 256,
+    decoding: true
+}
+
+// Test tone response curves from ICC profiles
+gamma_table! {
+    name: TEST_ICC_RED_TABLE,
+    entry_type: u16,
+    icc_profile: "tests/data/display.icc",
+    channel: red,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_ICC_GREEN_TABLE,
+    entry_type: u16,
+    icc_profile: "tests/data/display.icc",
+    channel: green,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_ICC_TABLE,
+    entry_type: u16,
+    icc_profile: "tests/data/display.icc",
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_ICC_BLUE_DECODING_TABLE,
+    entry_type: u8,
+    icc_profile: "tests/data/display.icc",
+    channel: blue,
+    size: 256,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_ICC_BLUE_POWER_TABLE,
+    entry_type: u8,
+    gamma: 1.8,
+    size: 256,
+    decoding: true
+}
+
+gamma_table! {
+    name: TEST_ICC_GRAY_DECODING_TABLE,
+    entry_type: u8,
+    icc_profile: "tests/data/gray.icc",
+    size: 256,
+    decoding: true
+}
+
+// Test 1D .cube LUTs, read per channel and written back out
+gamma_table! {
+    name: TEST_CUBE_RED_TABLE,
+    entry_type: u8,
+    cube_lut: "tests/data/warm_film.cube",
+    channel: red,
+    size: 9
+}
+
+gamma_table! {
+    name: TEST_CUBE_GREEN_TABLE,
+    entry_type: u8,
+    cube_lut: "tests/data/warm_film.cube",
+    channel: green,
+    size: 5,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_CUBE_GAMMA_TABLE,
+    entry_type: u8,
+    cube_lut: "tests/data/gamma_22.cube",
+    size: 17,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_CUBE_POWER_TABLE,
+    entry_type: u8,
+    gamma: 2.2,
+    size: 17,
+    max_value: 255
+}
+
+gamma_table! {
+    name: TEST_CUBE_EXPORT_TABLE,
+    entry_type: u8,
+    gamma: 2.2,
+    size: 5,
+    max_value: 255,
+    export_cube: true
+}
+
+// Test decibel volume tapers, with the default -60 dB floor and a custom range
+gamma_table! {
+    name: TEST_DB_TAPER_TABLE,
+    entry_type: u16,
+    curve: db_taper,
+    size: 256,
+    max_value: 65535
+}
+
+gamma_table! {
+    name: TEST_DB_TAPER_40_TABLE,
+    entry_type: u8,
+    curve: db_taper,
+    min_db: -40.0,
+    max_db: 0.0,
+    size: 101,
+    max_value: 255
+}
+
 #[test]
 fn test_encoding_table_properties() {
     // Test gamma encoding table properties
@@ -148,6 +830,522 @@ fn test_minimum_size_table() {
     assert!(TEST_MINIMUM_SIZE_TABLE[2] >= TEST_MINIMUM_SIZE_TABLE[1]);
 }
 
+#[test]
+fn test_srgb_tables() {
+    assert_eq!(TEST_SRGB_TO_LINEAR_TABLE[0], 0);
+    assert_eq!(TEST_SRGB_TO_LINEAR_TABLE[255], 65535);
+    assert_eq!(TEST_LINEAR_TO_SRGB_TABLE[0], 0);
+    assert_eq!(TEST_LINEAR_TO_SRGB_TABLE[255], 255);
+
+    // Linear segment near black: 10/255 is below the 0.04045 threshold
+    assert_eq!(
+        TEST_SRGB_TO_LINEAR_TABLE[10],
+        (10.0 / 255.0 / 12.92 * 65535.0_f64).round() as u16
+    );
+    // Power segment: sRGB 50% grey is about 21.4% linear light
+    assert_eq!(TEST_SRGB_TO_LINEAR_TABLE[128], 14_146);
+
+    for i in 1..256 {
+        assert!(TEST_SRGB_TO_LINEAR_TABLE[i] >= TEST_SRGB_TO_LINEAR_TABLE[i - 1]);
+        assert!(TEST_LINEAR_TO_SRGB_TABLE[i] >= TEST_LINEAR_TO_SRGB_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_piecewise_table_matches_srgb() {
+    assert_eq!(TEST_PIECEWISE_SRGB_TABLE, TEST_SRGB_TO_LINEAR_TABLE);
+}
+
+#[test]
+fn test_video_transfer_tables() {
+    // Linear segment of the OETF: V = 4.5 L
+    assert_eq!(TEST_REC709_OETF_TABLE[2], 9);
+    assert_eq!(TEST_REC709_OETF_TABLE[1023], 1023);
+    assert_eq!(TEST_REC2020_12BIT_OETF_TABLE[4095], 4095);
+
+    // BT.1886 black level lifts the first entry to Lb / Lw
+    assert_eq!(TEST_BT1886_TABLE[0], 10);
+    assert_eq!(TEST_BT1886_TABLE[255], 10000);
+
+    for i in 1..TEST_BT1886_TABLE.len() {
+        assert!(TEST_BT1886_TABLE[i] > TEST_BT1886_TABLE[i - 1]);
+    }
+    for i in 1..TEST_REC709_OETF_TABLE.len() {
+        assert!(TEST_REC709_OETF_TABLE[i] >= TEST_REC709_OETF_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_pq_tables() {
+    // Output is in cd/m² because max_value equals peak_nits
+    assert_eq!(TEST_PQ_TO_NITS_TABLE[0], 0);
+    assert_eq!(TEST_PQ_TO_NITS_TABLE[520], 100); // PQ code 520 is about 100 cd/m²
+    assert_eq!(TEST_PQ_TO_NITS_TABLE[1023], 1000); // Signals above the peak clip
+
+    // Input is in cd/m² because size - 1 equals peak_nits
+    assert_eq!(TEST_NITS_TO_PQ_TABLE[0], 0);
+    assert_eq!(TEST_NITS_TO_PQ_TABLE[100], 520);
+    assert_eq!(TEST_NITS_TO_PQ_TABLE[1000], 769);
+
+    for i in 1..TEST_PQ_TO_NITS_TABLE.len() {
+        assert!(TEST_PQ_TO_NITS_TABLE[i] >= TEST_PQ_TO_NITS_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_hlg_tables() {
+    assert_eq!(TEST_HLG_OETF_TABLE[0], 0);
+    assert_eq!(TEST_HLG_OETF_TABLE[1023], 1023);
+    // Square-root segment below 1/12 scene light: sqrt(3 * 31 / 1023) * 1023
+    assert_eq!(TEST_HLG_OETF_TABLE[31], 308);
+
+    assert_eq!(TEST_HLG_DISPLAY_TABLE[0], 0);
+    assert_eq!(TEST_HLG_DISPLAY_TABLE[1023], 65535);
+
+    for i in 1..1024 {
+        assert!(TEST_HLG_OETF_TABLE[i] >= TEST_HLG_OETF_TABLE[i - 1]);
+        assert!(TEST_HLG_DISPLAY_TABLE[i] >= TEST_HLG_DISPLAY_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_cie_lightness_tables() {
+    // Indexed by L*, output in hundredths of a percent luminance
+    assert_eq!(TEST_LIGHTNESS_TABLE[0], 0);
+    assert_eq!(TEST_LIGHTNESS_TABLE[4], 44); // Linear segment: 4 / 903.3
+    assert_eq!(TEST_LIGHTNESS_TABLE[50], 1842);
+    assert_eq!(TEST_LIGHTNESS_TABLE[100], 10000);
+
+    // 18.4% luminance is perceived as mid-grey
+    assert_eq!(TEST_LUMINANCE_TO_LIGHTNESS_TABLE[47], 50);
+    assert_eq!(TEST_LUMINANCE_TO_LIGHTNESS_TABLE[255], 100);
+
+    for i in 1..TEST_LIGHTNESS_TABLE.len() {
+        assert!(TEST_LIGHTNESS_TABLE[i] > TEST_LIGHTNESS_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_dali_tables() {
+    // Output in hundredths of a percent of full power
+    assert_eq!(TEST_DALI_TABLE[0], 0); // Off
+    assert_eq!(TEST_DALI_TABLE[1], 10); // 0.1%
+    assert_eq!(TEST_DALI_TABLE[170], 1009); // 10.09%
+    assert_eq!(TEST_DALI_TABLE[254], 10000);
+
+    // Indexed in tenths of a percent; only zero light switches off
+    assert_eq!(TEST_LIGHT_TO_DALI_TABLE[0], 0);
+    assert_eq!(TEST_LIGHT_TO_DALI_TABLE[1], 1);
+    assert_eq!(TEST_LIGHT_TO_DALI_TABLE[100], 170);
+    assert_eq!(TEST_LIGHT_TO_DALI_TABLE[1000], 254);
+
+    for i in 1..TEST_DALI_TABLE.len() {
+        assert!(TEST_DALI_TABLE[i] >= TEST_DALI_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_dmx_dimmer_tables() {
+    for (i, &value) in TEST_DMX_LINEAR_TABLE.iter().enumerate() {
+        assert_eq!(usize::from(value), i);
+    }
+
+    // Half fader is a quarter of the output on square law, 70.7% on inverse square law
+    assert_eq!(TEST_DMX_SQUARE_LAW_TABLE[128], 16513);
+    assert_eq!(TEST_DMX_INVERSE_SQUARE_LAW_TABLE[128], 181);
+    assert_eq!(TEST_DMX_S_CURVE_TABLE[64], 40);
+    assert_eq!(TEST_DMX_S_CURVE_TABLE[128], 128);
+
+    // Decoding undoes the S-curve
+    for (i, &output) in (0u8..=255).zip(TEST_DMX_S_CURVE_TABLE.iter()) {
+        let level = TEST_DMX_S_CURVE_DECODING_TABLE[usize::from(output)];
+        assert!(level.abs_diff(i) <= 8, "{i} -> {level}");
+    }
+
+    for table in [
+        &TEST_DMX_INVERSE_SQUARE_LAW_TABLE,
+        &TEST_DMX_S_CURVE_TABLE,
+        &TEST_DMX_S_CURVE_DECODING_TABLE,
+    ] {
+        assert_eq!(table[0], 0);
+        assert_eq!(table[255], 255);
+        for i in 1..table.len() {
+            assert!(table[i] >= table[i - 1]);
+        }
+    }
+}
+
+#[test]
+fn test_camera_log_tables() {
+    // Codes below black clamp to 0, code 1023 is full scale
+    assert_eq!(TEST_SLOG3_TO_LINEAR_TABLE[0], 0);
+    assert_eq!(TEST_SLOG3_TO_LINEAR_TABLE[1023], 65535);
+    assert_eq!(TEST_CINEON_TO_LINEAR_TABLE[95], 0);
+    assert_eq!(TEST_CINEON_TO_LINEAR_TABLE[1023], 65535);
+
+    // Log encoding lifts linear black to the camera's black code value
+    assert_eq!(TEST_LINEAR_TO_LOGC3_TABLE[0], 95); // 0.092809 * 1023
+    assert_eq!(TEST_LINEAR_TO_LOGC3_TABLE[1023], 1023);
+
+    for i in 1..1024 {
+        assert!(TEST_SLOG3_TO_LINEAR_TABLE[i] >= TEST_SLOG3_TO_LINEAR_TABLE[i - 1]);
+        assert!(TEST_LINEAR_TO_LOGC3_TABLE[i] >= TEST_LINEAR_TO_LOGC3_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_aces_log_tables() {
+    // ACEScc of linear 0 is (log2(2^-16) + 9.72) / 17.52
+    assert_eq!(TEST_LINEAR_TO_ACESCC_TABLE[0], -3584);
+    assert_eq!(TEST_LINEAR_TO_ACESCC_TABLE[1023], 5548);
+
+    // The ACEScct toe decodes code 0 to a small negative value, code 1.0 to 222.86
+    assert_eq!(TEST_ACESCCT_TO_LINEAR_TABLE[0], -7);
+    assert_eq!(TEST_ACESCCT_TO_LINEAR_TABLE[1023], 222_861);
+
+    for i in 1..1024 {
+        assert!(TEST_LINEAR_TO_ACESCC_TABLE[i] >= TEST_LINEAR_TO_ACESCC_TABLE[i - 1]);
+        assert!(TEST_ACESCCT_TO_LINEAR_TABLE[i] >= TEST_ACESCCT_TO_LINEAR_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_gsdf_tables() {
+    // Equal steps in JND index, output relative to the display's luminance range
+    assert_eq!(TEST_GSDF_TABLE[0], 0);
+    assert_eq!(TEST_GSDF_TABLE[64], 6);
+    assert_eq!(TEST_GSDF_TABLE[128], 27);
+    assert_eq!(TEST_GSDF_TABLE[192], 89);
+    assert_eq!(TEST_GSDF_TABLE[255], 255);
+
+    // Perceptually uniform levels are dense near black
+    assert_eq!(TEST_LUMINANCE_TO_GSDF_TABLE[0], 0);
+    assert_eq!(TEST_LUMINANCE_TO_GSDF_TABLE[1], 31);
+    assert_eq!(TEST_LUMINANCE_TO_GSDF_TABLE[512], 855);
+    assert_eq!(TEST_LUMINANCE_TO_GSDF_TABLE[1023], 1023);
+
+    for i in 1..TEST_LUMINANCE_TO_GSDF_TABLE.len() {
+        assert!(TEST_LUMINANCE_TO_GSDF_TABLE[i] >= TEST_LUMINANCE_TO_GSDF_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_tone_map_tables() {
+    // Reinhard maps the white point to full scale, then encodes with 1/2.2
+    assert_eq!(TEST_REINHARD_TABLE[0], 0);
+    assert_eq!(TEST_REINHARD_TABLE[64], 191); // Linear 1.0
+    assert_eq!(TEST_REINHARD_TABLE[255], 255);
+
+    // Hable's default white point is 11.2
+    assert_eq!(TEST_HABLE_TABLE[0], 0);
+    assert_eq!(TEST_HABLE_TABLE[512], 53592);
+    assert_eq!(TEST_HABLE_TABLE[1023], 65535);
+
+    // The ACES fit stays just below white at linear 16
+    assert_eq!(TEST_ACES_FITTED_TABLE[0], 0);
+    assert_eq!(TEST_ACES_FITTED_TABLE[255], 254);
+
+    for i in 1..256 {
+        assert!(TEST_REINHARD_TABLE[i] >= TEST_REINHARD_TABLE[i - 1]);
+        assert!(TEST_ACES_FITTED_TABLE[i] >= TEST_ACES_FITTED_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_companding_tables() {
+    // G.711 wire codes, decoded to 16-bit samples
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0xFF], 0);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0x7F], 0);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0xFE], 8);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0x80], 32124);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0x00], -32124);
+    assert_eq!(TEST_A_LAW_EXPAND_TABLE[0xD5], 8);
+    assert_eq!(TEST_A_LAW_EXPAND_TABLE[0x55], -8);
+    assert_eq!(TEST_A_LAW_EXPAND_TABLE[0xAA], 32256);
+    assert_eq!(TEST_A_LAW_EXPAND_TABLE[0x2A], -32256);
+
+    let mu_law = |sample: i16| TEST_MU_LAW_COMPRESS_TABLE[(sample as u16 & 0x3FFF) as usize];
+    assert_eq!(mu_law(0), 0xFF);
+    assert_eq!(mu_law(-1), 0x7E);
+    assert_eq!(mu_law(8191), 0x80);
+    assert_eq!(mu_law(-8192), 0x00);
+    let a_law = |sample: i16| TEST_A_LAW_COMPRESS_TABLE[(sample as u16 & 0x1FFF) as usize];
+    assert_eq!(a_law(0), 0xD5);
+    assert_eq!(a_law(-1), 0x55);
+    assert_eq!(a_law(4095), 0xAA);
+    assert_eq!(a_law(-4096), 0x2A);
+
+    // 100 falls in a step of 8 whose middle is 99, or 396 in 16 bits
+    assert_eq!(mu_law(100), 0xDF);
+    assert_eq!(mu_law(-100), 0x5F);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0xDF], 396);
+    assert_eq!(TEST_MU_LAW_EXPAND_TABLE[0x5F], -396);
+
+    // Without the wire inversion, codes 0..=127 rise and 128..=255 are their negatives
+    assert_eq!(TEST_MU_LAW_SIGN_MAGNITUDE_TABLE[0x7F], 8031);
+    for i in 1..128 {
+        assert!(TEST_MU_LAW_SIGN_MAGNITUDE_TABLE[i] > TEST_MU_LAW_SIGN_MAGNITUDE_TABLE[i - 1]);
+        assert_eq!(
+            TEST_MU_LAW_SIGN_MAGNITUDE_TABLE[128 + i],
+            -TEST_MU_LAW_SIGN_MAGNITUDE_TABLE[i]
+        );
+    }
+}
+
+#[test]
+fn test_sigmoid_tables() {
+    // Symmetric around the midpoint, with darker shadows and brighter highlights
+    assert_eq!(TEST_SIGMOID_TABLE[0], 0);
+    assert_eq!(TEST_SIGMOID_TABLE[64], 18);
+    assert_eq!(TEST_SIGMOID_TABLE[191], 237);
+    assert_eq!(TEST_SIGMOID_TABLE[255], 255);
+    for (low, high) in TEST_SIGMOID_TABLE
+        .iter()
+        .zip(TEST_SIGMOID_TABLE.iter().rev())
+    {
+        assert_eq!(u16::from(*low) + u16::from(*high), 255);
+    }
+
+    assert_eq!(TEST_SIGMOID_ENCODED_TABLE[0], 0);
+    assert_eq!(TEST_SIGMOID_ENCODED_TABLE[18], 646);
+    assert_eq!(TEST_SIGMOID_ENCODED_TABLE[100], 1000);
+
+    for i in 1..TEST_SIGMOID_TABLE.len() {
+        assert!(TEST_SIGMOID_TABLE[i] >= TEST_SIGMOID_TABLE[i - 1]);
+    }
+    for i in 1..TEST_SIGMOID_ENCODED_TABLE.len() {
+        assert!(TEST_SIGMOID_ENCODED_TABLE[i] >= TEST_SIGMOID_ENCODED_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_easing_tables() {
+    // Symmetric around the middle frame
+    assert_eq!(TEST_EASE_IN_OUT_CUBIC_TABLE[0], 0);
+    assert_eq!(TEST_EASE_IN_OUT_CUBIC_TABLE[16], 16); // 4 * 0.25^3
+    assert_eq!(TEST_EASE_IN_OUT_CUBIC_TABLE[32], 128);
+    assert_eq!(TEST_EASE_IN_OUT_CUBIC_TABLE[48], 239);
+    assert_eq!(TEST_EASE_IN_OUT_CUBIC_TABLE[64], 255);
+    for i in 1..TEST_EASE_IN_OUT_CUBIC_TABLE.len() {
+        assert!(TEST_EASE_IN_OUT_CUBIC_TABLE[i] >= TEST_EASE_IN_OUT_CUBIC_TABLE[i - 1]);
+    }
+
+    // The ball first lands at t = 1 / 2.75, just after index 36, then bounces back up
+    assert_eq!(TEST_EASE_OUT_BOUNCE_TABLE[36], 9801);
+    assert_eq!(TEST_EASE_OUT_BOUNCE_TABLE[55], 7502);
+    assert_eq!(TEST_EASE_OUT_BOUNCE_TABLE[100], 10000);
+
+    // Back pulls below zero before accelerating to the end
+    assert_eq!(TEST_EASE_IN_BACK_TABLE[0], 0);
+    assert_eq!(TEST_EASE_IN_BACK_TABLE[4], -99);
+    assert_eq!(TEST_EASE_IN_BACK_TABLE[10], 1000);
+}
+
+#[test]
+fn test_bipolar_tables() {
+    // Centered on index 127, odd symmetric around it
+    assert_eq!(TEST_BIPOLAR_TABLE[0], -127);
+    assert_eq!(TEST_BIPOLAR_TABLE[127], 0);
+    assert_eq!(TEST_BIPOLAR_TABLE[190], 16); // 0.496^3
+    assert_eq!(TEST_BIPOLAR_TABLE[254], 127);
+    for i in 0..=127 {
+        assert_eq!(TEST_BIPOLAR_TABLE[127 + i], -TEST_BIPOLAR_TABLE[127 - i]);
+    }
+
+    // 10% of each half around index 500 is dead
+    assert_eq!(TEST_BIPOLAR_DEAD_ZONE_TABLE[0], -1000);
+    assert!(TEST_BIPOLAR_DEAD_ZONE_TABLE[450..=550]
+        .iter()
+        .all(|&value| value == 0));
+    assert_eq!(TEST_BIPOLAR_DEAD_ZONE_TABLE[551], 0); // (0.002 / 0.9)^2
+    assert_eq!(TEST_BIPOLAR_DEAD_ZONE_TABLE[775], 250);
+    assert_eq!(TEST_BIPOLAR_DEAD_ZONE_TABLE[225], -250);
+    assert_eq!(TEST_BIPOLAR_DEAD_ZONE_TABLE[1000], 1000);
+
+    for i in 1..TEST_BIPOLAR_DEAD_ZONE_TABLE.len() {
+        assert!(TEST_BIPOLAR_DEAD_ZONE_TABLE[i] >= TEST_BIPOLAR_DEAD_ZONE_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_dot_gain_tables() {
+    // Indexed and valued in percent dot area
+    assert_eq!(TEST_PRINTED_TONE_TABLE[50], 68);
+    assert_eq!(TEST_DOT_GAIN_TABLE[50], 34);
+    assert_eq!(TEST_DOT_GAIN_TABLE[0], 0);
+    assert_eq!(TEST_DOT_GAIN_TABLE[100], 100);
+
+    // Compensated dots print close to the requested tone
+    for (target, &dot) in TEST_DOT_GAIN_TABLE.iter().enumerate() {
+        let printed = TEST_PRINTED_TONE_TABLE[usize::from(dot)];
+        assert!(
+            usize::from(printed).abs_diff(target) <= 2,
+            "{target} -> {printed}"
+        );
+    }
+}
+
+#[test]
+fn test_expression_tables() {
+    assert_eq!(TEST_EXPR_GAMMA_TABLE, TEST_GAMMA_ENCODING_TABLE);
+    assert_eq!(TEST_EXPR_SRGB_TABLE, TEST_SRGB_TO_LINEAR_TABLE);
+    assert_eq!(TEST_EXPR_WAVE_TABLE, [0, 71, 100, 71, 0, -71, -100, -71, 0]);
+}
+
+#[test]
+fn test_inverted_tables() {
+    assert_eq!(TEST_INVERTED_GAMMA_TABLE, TEST_GAMMA_DECODING_TABLE);
+    assert_eq!(TEST_INVERTED_SRGB_TABLE, TEST_LINEAR_TO_SRGB_TABLE);
+
+    // The inverse undoes the sigmoid, up to rounding where it is steep or flat
+    assert_eq!(TEST_INVERTED_SIGMOID_TABLE[18], 64);
+    assert_eq!(TEST_INVERTED_SIGMOID_TABLE[64], 100);
+    assert_eq!(TEST_INVERTED_SIGMOID_TABLE[191], 155);
+    for (i, &value) in TEST_SIGMOID_TABLE.iter().enumerate().take(218).skip(38) {
+        let round_trip = TEST_INVERTED_SIGMOID_TABLE[usize::from(value)];
+        assert!(
+            usize::from(round_trip).abs_diff(i) <= 1,
+            "{i} -> {round_trip}"
+        );
+    }
+    assert_eq!(TEST_INVERTED_SIGMOID_TABLE[128], 128);
+
+    // A falling curve: the index runs from its first output (1.0) to its last (0.0)
+    assert_eq!(TEST_INVERTED_EXPR_TABLE[0], 0);
+    assert_eq!(TEST_INVERTED_EXPR_TABLE[50], 2500);
+    assert_eq!(TEST_INVERTED_EXPR_TABLE[100], 10000);
+}
+
+#[test]
+fn test_pipeline_tables() {
+    assert_eq!(TEST_PIPELINE_SRGB_TABLE, TEST_SRGB_TO_LINEAR_TABLE);
+    assert_eq!(TEST_TRANSCODE_TABLE, TEST_TRANSCODE_POWER_TABLE);
+
+    // Full white is dimmed to 0.8^(1/2.5) = 91.5%, mid-grey to (0.214 * 0.8)^(1/2.5)
+    assert_eq!(TEST_PIPELINE_LED_TABLE[0], 0);
+    assert_eq!(TEST_PIPELINE_LED_TABLE[128], 126);
+    assert_eq!(TEST_PIPELINE_LED_TABLE[255], 233);
+    for i in 1..TEST_PIPELINE_LED_TABLE.len() {
+        assert!(TEST_PIPELINE_LED_TABLE[i] >= TEST_PIPELINE_LED_TABLE[i - 1]);
+    }
+}
+
+#[test]
+fn test_measured_tables() {
+    assert_eq!(TEST_MEASURED_CSV_TABLE, TEST_MEASURED_INLINE_TABLE);
+
+    // Linear interpolation between the normalized points
+    assert_eq!(TEST_MEASURED_LINEAR_TABLE[0], 0);
+    assert_eq!(TEST_MEASURED_LINEAR_TABLE[64], 25);
+    assert_eq!(TEST_MEASURED_LINEAR_TABLE[96], 69);
+    assert_eq!(TEST_MEASURED_LINEAR_TABLE[128], 114);
+    assert_eq!(TEST_MEASURED_LINEAR_TABLE[240], 255);
+
+    // The monotone spline passes through the points and never decreases
+    for (index, expected) in [(0, 0), (64, 25), (128, 114), (224, 255), (255, 255)] {
+        assert_eq!(TEST_MEASURED_CSV_TABLE[index], expected);
+    }
+    for table in [&TEST_MEASURED_CSV_TABLE, &TEST_MEASURED_LINEAR_TABLE] {
+        assert!(table.windows(2).all(|pair| pair[1] >= pair[0]));
+    }
+    assert!(TEST_MEASURED_DECODING_TABLE
+        .windows(2)
+        .all(|pair| pair[1] >= pair[0]));
+
+    // Full output is first reached at PWM 224, where the plateau starts
+    assert_eq!(TEST_MEASURED_DECODING_TABLE[0], 0);
+    assert_eq!(TEST_MEASURED_DECODING_TABLE[255], 899);
+}
+
+#[test]
+fn test_fit_tables() {
+    assert!((TEST_FIT_TABLE_GAMMA - 2.4).abs() < 1e-6);
+    const { assert!(TEST_FIT_TABLE_RMS_ERROR < 1e-9) };
+    assert_eq!(TEST_FIT_TABLE, TEST_FIT_POWER_TABLE);
+
+    // The table inverts the fitted model, black offset included: levels below black decode to 0
+    assert!((TEST_FIT_BLACK_TABLE_GAMMA - 2.2).abs() < 1e-4);
+    assert!((TEST_FIT_BLACK_TABLE_BLACK_OFFSET - 0.05).abs() < 1e-4);
+    const { assert!(TEST_FIT_BLACK_TABLE_RMS_ERROR < 1e-5) };
+    assert!(TEST_FIT_BLACK_TABLE[..=12].iter().all(|&value| value == 0));
+    assert_eq!(TEST_FIT_BLACK_TABLE[64], 126);
+    assert_eq!(TEST_FIT_BLACK_TABLE[128], 182);
+    assert_eq!(TEST_FIT_BLACK_TABLE[255], 255);
+    assert_ne!(TEST_FIT_BLACK_TABLE, TEST_GAMMA_DECODING_TABLE);
+}
+
+#[test]
+fn test_icc_tables() {
+    // rTRC is a curv gamma of 563 / 256
+    assert_eq!(TEST_ICC_RED_TABLE[0], 0);
+    assert_eq!(TEST_ICC_RED_TABLE[64], 3134);
+    assert_eq!(TEST_ICC_RED_TABLE[128], 14394);
+    assert_eq!(TEST_ICC_RED_TABLE[192], 35111);
+    assert_eq!(TEST_ICC_RED_TABLE[255], 65535);
+
+    // gTRC is the sRGB para curve, with s15Fixed16 parameters
+    for (icc, exact) in TEST_ICC_GREEN_TABLE.iter().zip(TEST_SRGB_TO_LINEAR_TABLE) {
+        assert!(icc.abs_diff(exact) <= 2, "{icc} != {exact}");
+    }
+
+    // Without a channel, an RGB profile gives all three tables
+    assert_eq!(TEST_ICC_TABLE_RED, TEST_ICC_RED_TABLE);
+    assert_eq!(TEST_ICC_TABLE_GREEN, TEST_ICC_GREEN_TABLE);
+    assert_eq!(TEST_ICC_TABLE_BLUE[255], 65535);
+
+    // bTRC is a sampled x^1.8, decoded by bisection
+    for (icc, power) in TEST_ICC_BLUE_DECODING_TABLE
+        .iter()
+        .zip(TEST_ICC_BLUE_POWER_TABLE)
+    {
+        assert!(icc.abs_diff(power) <= 1, "{icc} != {power}");
+    }
+
+    // A grayscale profile needs no channel; kTRC is a curv gamma of 461 / 256
+    assert_eq!(TEST_ICC_GRAY_DECODING_TABLE[0], 0);
+    assert_eq!(TEST_ICC_GRAY_DECODING_TABLE[64], 118);
+    assert_eq!(TEST_ICC_GRAY_DECODING_TABLE[128], 174);
+    assert_eq!(TEST_ICC_GRAY_DECODING_TABLE[192], 218);
+    assert_eq!(TEST_ICC_GRAY_DECODING_TABLE[255], 255);
+}
+
+#[test]
+fn test_cube_tables() {
+    // Resampled from 5 rows to 9 entries, with max_value defaulting to 8
+    assert_eq!(TEST_CUBE_RED_TABLE, [0, 1, 3, 4, 4, 5, 6, 7, 8]);
+    assert_eq!(TEST_CUBE_GREEN_TABLE, [0, 64, 128, 191, 255]);
+    // Identical columns need no channel
+    assert_eq!(TEST_CUBE_GAMMA_TABLE, TEST_CUBE_POWER_TABLE);
+
+    assert_eq!(TEST_CUBE_EXPORT_TABLE, [0, 12, 55, 135, 255]);
+    assert_eq!(
+        TEST_CUBE_EXPORT_TABLE_CUBE,
+        "TITLE \"TEST_CUBE_EXPORT_TABLE\"\nLUT_1D_SIZE 5\n\
+         0.000000 0.000000 0.000000\n\
+         0.047059 0.047059 0.047059\n\
+         0.215686 0.215686 0.215686\n\
+         0.529412 0.529412 0.529412\n\
+         1.000000 1.000000 1.000000\n"
+    );
+}
+
+#[test]
+fn test_db_taper_tables() {
+    // The first entry mutes, the rest rise in equal dB steps to full scale
+    assert_eq!(TEST_DB_TAPER_TABLE[0], 0);
+    assert_eq!(TEST_DB_TAPER_TABLE[1], 67); // -59.76 dB
+    assert_eq!(TEST_DB_TAPER_TABLE[128], 2101); // -29.88 dB
+    assert_eq!(TEST_DB_TAPER_TABLE[255], 65535);
+
+    assert_eq!(TEST_DB_TAPER_40_TABLE[0], 0);
+    assert_eq!(TEST_DB_TAPER_40_TABLE[50], 26); // -20 dB
+    assert_eq!(TEST_DB_TAPER_40_TABLE[100], 255);
+
+    for i in 1..TEST_DB_TAPER_TABLE.len() {
+        assert!(TEST_DB_TAPER_TABLE[i] > TEST_DB_TAPER_TABLE[i - 1]);
+    }
+}
+
 #[test]
 fn test_compile_fail() {
     let t = trybuild::TestCases::new();